@@ -0,0 +1,56 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use cs::binary_search;
+use cs::generators::{Distribution, generate, powers_of_ten};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hint::black_box;
+
+const MAX_LEN: usize = 10_000_000;
+const QUERIES: usize = 1000;
+
+// Each iteration runs QUERIES searches for random targets, so that the time is
+// not dominated by a single lucky or unlucky path through the input
+fn searches(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    for distribution in [Distribution::Sorted, Distribution::FewUnique] {
+        let mut group = c.benchmark_group(format!("binary_search/{}", distribution.name()));
+        for len in powers_of_ten(MAX_LEN) {
+            let mut input = generate(distribution, len, &mut rng);
+            input.sort_unstable();
+            let max = input.last().copied().unwrap_or(0);
+            let targets: Vec<i32> = (0..QUERIES)
+                .map(|_| rng.random_range(-1..=max + 1))
+                .collect();
+
+            group.bench_with_input(BenchmarkId::new("lower_bound", len), &input, |b, input| {
+                b.iter(|| {
+                    for target in &targets {
+                        black_box(binary_search::lower_bound(input, target));
+                    }
+                })
+            });
+            group.bench_with_input(BenchmarkId::new("equal_range", len), &input, |b, input| {
+                b.iter(|| {
+                    for target in &targets {
+                        black_box(binary_search::equal_range(input, target));
+                    }
+                })
+            });
+            group.bench_with_input(
+                BenchmarkId::new("std_partition_point", len),
+                &input,
+                |b, input| {
+                    b.iter(|| {
+                        for target in &targets {
+                            black_box(input.partition_point(|value| value < target));
+                        }
+                    })
+                },
+            );
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, searches);
+criterion_main!(benches);
@@ -0,0 +1,188 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use cs::bk_tree::BkTree;
+use cs::fuzzy_trie::FuzzyTrie;
+use cs::generators::{powers_of_ten, random_string};
+use cs::levenshtein_distance;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::hint::black_box;
+
+// The DP is O(m * n), so 10^4 characters already take 10^8 steps
+const MAX_LEN: usize = 10_000;
+
+// `distance` runs the DP for any costs but UnitCosts, even for weights of 1
+const DP_UNIT_COSTS: levenshtein_distance::WeightedCosts = levenshtein_distance::WeightedCosts {
+    insert: 1,
+    delete: 1,
+    substitute: 1,
+};
+
+const ASCII: [char; 16] = [
+    ' ', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o',
+];
+// Not ASCII, so `calculate` looks the chars up by binary search instead of a table
+const GREEK: [char; 16] = [
+    'α', 'β', 'γ', 'δ', 'ε', 'ζ', 'η', 'θ', 'ι', 'κ', 'λ', 'μ', 'ν', 'ξ', 'ο', 'π',
+];
+
+fn bench_pair(
+    group: &mut criterion::BenchmarkGroup<criterion::measurement::WallTime>,
+    len: usize,
+    lhs: &str,
+    rhs: &str,
+) {
+    group.bench_with_input(
+        BenchmarkId::new("calculate", len),
+        &(lhs, rhs),
+        |b, (lhs, rhs)| b.iter(|| levenshtein_distance::calculate(black_box(lhs), black_box(rhs))),
+    );
+
+    let lhs: Vec<char> = lhs.chars().collect();
+    let rhs: Vec<char> = rhs.chars().collect();
+    group.bench_with_input(
+        BenchmarkId::new("dynamic_programming", len),
+        &(&lhs, &rhs),
+        |b, (lhs, rhs)| {
+            b.iter(|| {
+                levenshtein_distance::distance(black_box(lhs), black_box(rhs), &DP_UNIT_COSTS)
+            })
+        },
+    );
+    group.bench_with_input(
+        BenchmarkId::new("bit_parallel", len),
+        &(&lhs, &rhs),
+        |b, (lhs, rhs)| {
+            b.iter(|| levenshtein_distance::bit_parallel_distance(black_box(lhs), black_box(rhs)))
+        },
+    );
+}
+
+fn distances(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    let alphabets: [(&str, &[char]); 3] = [
+        ("dna", &['a', 'c', 'g', 't']),
+        ("ascii", &ASCII),
+        ("greek", &GREEK),
+    ];
+    for (name, alphabet) in alphabets {
+        let mut group = c.benchmark_group(format!("levenshtein_distance/{name}"));
+        for len in powers_of_ten(MAX_LEN) {
+            let lhs = random_string(len, alphabet, &mut rng);
+            let rhs = random_string(len, alphabet, &mut rng);
+            bench_pair(&mut group, len, &lhs, &rhs);
+        }
+        group.finish();
+    }
+}
+
+// Short strings, where building the masks of the bit-parallel algorithm is most
+// of its cost. The DP only wins up to 2 chars, so `calculate` runs it up to
+// there. At 3 chars the bit-parallel algorithm wins with the byte table for
+// ASCII and ties with the binary search for other chars. It wins with both
+// from 4 chars on.
+fn crossover(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    for (name, alphabet) in [("ascii", &ASCII), ("greek", &GREEK)] {
+        let mut group = c.benchmark_group(format!("levenshtein_distance/crossover_{name}"));
+        for len in [2, 3, 4, 6, 8, 10, 12, 16, 24, 32, 64] {
+            let lhs = random_string(len, alphabet, &mut rng);
+            let rhs = random_string(len, alphabet, &mut rng);
+            bench_pair(&mut group, len, &lhs, &rhs);
+        }
+        group.finish();
+    }
+}
+
+// Random dictionary words of 4 to 11 letters
+fn random_words(len: usize, rng: &mut StdRng) -> Vec<String> {
+    let alphabet: Vec<char> = ('a'..='z').collect();
+    (0..len)
+        .map(|_| random_string(rng.random_range(4..12), &alphabet, rng))
+        .collect()
+}
+
+// The words closest to a typo, by comparing it with every word or through a
+// BK-tree. The query is a dictionary word with one letter changed: a query far
+// from every word prunes little and visits most of the tree.
+fn bk_tree_search(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    let mut group = c.benchmark_group("levenshtein_distance/bk_tree");
+    for len in powers_of_ten(100_000).skip(2) {
+        let words = random_words(len, &mut rng);
+        let mut tree = BkTree::new();
+        for word in &words {
+            tree.insert(word);
+        }
+        let mut query: Vec<char> = words[len / 2].chars().collect();
+        query[1] = if query[1] == 'z' { 'a' } else { 'z' };
+        let query: String = query.into_iter().collect();
+
+        group.bench_with_input(BenchmarkId::new("scan_within", len), &words, |b, words| {
+            b.iter(|| {
+                words
+                    .iter()
+                    .filter(|word| levenshtein_distance::calculate(black_box(&query), word) <= 2)
+                    .count()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("find_within", len), &tree, |b, tree| {
+            b.iter(|| tree.find_within(black_box(&query), 2))
+        });
+        group.bench_with_input(BenchmarkId::new("scan_nearest", len), &words, |b, words| {
+            b.iter(|| {
+                words
+                    .iter()
+                    .map(|word| levenshtein_distance::calculate(black_box(&query), word))
+                    .min()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("nearest", len), &tree, |b, tree| {
+            b.iter(|| tree.nearest(black_box(&query), 1))
+        });
+    }
+    group.finish();
+}
+
+// Looking up the words within a small distance of a typo in a dictionary
+fn dictionary_search(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    let alphabet: Vec<char> = ('a'..='z').collect();
+    let k = 2;
+    let mut group = c.benchmark_group("levenshtein_distance/dictionary");
+    for len in powers_of_ten(100_000).skip(2) {
+        let words = random_words(len, &mut rng);
+        let mut trie = FuzzyTrie::new();
+        for word in &words {
+            trie.insert(word);
+        }
+        let query = random_string(8, &alphabet, &mut rng);
+
+        group.bench_with_input(BenchmarkId::new("scan", len), &words, |b, words| {
+            b.iter(|| {
+                words
+                    .iter()
+                    .filter(|word| levenshtein_distance::calculate(black_box(&query), word) <= k)
+                    .count()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("fuzzy_trie", len), &trie, |b, trie| {
+            b.iter(|| trie.find_within(black_box(&query), k))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("fuzzy_trie_prefix", len),
+            &trie,
+            |b, trie| b.iter(|| trie.find_prefix_within(black_box(&query[..4]), 1)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    distances,
+    crossover,
+    bk_tree_search,
+    dictionary_search
+);
+criterion_main!(benches);
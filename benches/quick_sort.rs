@@ -0,0 +1,41 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use cs::sort;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::hint::black_box;
+
+const LEN: usize = 100_000;
+
+// Three-way partitioning against the coin-flip Lomuto scheme on inputs with
+// few distinct values, where the two differ the most
+fn low_cardinality(c: &mut Criterion) {
+    let mut group = c.benchmark_group("quick_sort_low_cardinality");
+    for distinct in [2, 16, 256, LEN as u32] {
+        let mut rng = StdRng::seed_from_u64(distinct as u64);
+        let input: Vec<u32> = (0..LEN).map(|_| rng.random_range(0..distinct)).collect();
+
+        group.bench_with_input(
+            BenchmarkId::new("three_way", distinct),
+            &input,
+            |b, input| {
+                b.iter_batched_ref(
+                    || input.clone(),
+                    |data| sort::quick_sort(black_box(data), &mut rng),
+                    criterion::BatchSize::LargeInput,
+                )
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("lomuto", distinct), &input, |b, input| {
+            b.iter_batched_ref(
+                || input.clone(),
+                |data| sort::lomuto_quick_sort(black_box(data), &mut rng),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, low_cardinality);
+criterion_main!(benches);
@@ -0,0 +1,63 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use cs::generators::{Distribution, generate, powers_of_ten};
+use cs::{
+    left_nearest_number, longest_increasing_subsequence, longest_subsequence_below_or_equal_m,
+};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::hint::black_box;
+
+const MAX_LEN: usize = 10_000_000;
+// The longest increasing subsequence is O(n^2)
+const QUADRATIC_MAX_LEN: usize = 10_000;
+
+fn sequences(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    for distribution in Distribution::ALL {
+        let mut group = c.benchmark_group(format!("sequences/{}", distribution.name()));
+        for len in powers_of_ten(MAX_LEN) {
+            group.sample_size(if len >= 1_000_000 { 10 } else { 100 });
+            let input = generate(distribution, len, &mut rng);
+
+            group.bench_with_input(
+                BenchmarkId::new("left_nearest_number", len),
+                &input,
+                |b, input| b.iter(|| left_nearest_number::find(black_box(input))),
+            );
+            // Sums of the whole input stay within i32 for all the distributions
+            let limit = i32::try_from(len).unwrap() * 4;
+            group.bench_with_input(
+                BenchmarkId::new("longest_subsequence_below_or_equal_m", len),
+                &input,
+                |b, input| {
+                    b.iter(|| longest_subsequence_below_or_equal_m::find(black_box(input), limit))
+                },
+            );
+            if len <= QUADRATIC_MAX_LEN {
+                group.bench_with_input(
+                    BenchmarkId::new("longest_increasing_subsequence", len),
+                    &input,
+                    |b, input| b.iter(|| longest_increasing_subsequence::find(black_box(input))),
+                );
+                group.bench_with_input(
+                    BenchmarkId::new(
+                        "longest_increasing_subsequence_lexicographically_smallest",
+                        len,
+                    ),
+                    &input,
+                    |b, input| {
+                        b.iter(|| {
+                            longest_increasing_subsequence::find_lexicographically_smallest(
+                                black_box(input),
+                            )
+                        })
+                    },
+                );
+            }
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, sequences);
+criterion_main!(benches);
@@ -0,0 +1,242 @@
+use criterion::measurement::WallTime;
+use criterion::{
+    BatchSize, BenchmarkGroup, BenchmarkId, Criterion, criterion_group, criterion_main,
+};
+use cs::generators::{Distribution, generate, powers_of_ten, random_string};
+use cs::sort;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::hint::black_box;
+
+const MAX_LEN: usize = 10_000_000;
+// The O(n^2) sorts take minutes per sample beyond this
+const QUADRATIC_MAX_LEN: usize = 10_000;
+
+fn rng() -> StdRng {
+    StdRng::seed_from_u64(0x5EED)
+}
+
+// Long inputs take seconds per iteration, fewer samples keep the suite usable
+fn set_sample_size(group: &mut BenchmarkGroup<WallTime>, len: usize) {
+    group.sample_size(if len >= 1_000_000 { 10 } else { 100 });
+}
+
+fn bench_sort<T: Clone, S: FnMut(&mut [T])>(
+    group: &mut BenchmarkGroup<WallTime>,
+    name: &str,
+    input: &[T],
+    mut sort: S,
+) {
+    group.bench_with_input(BenchmarkId::new(name, input.len()), input, |b, input| {
+        b.iter_batched_ref(
+            || input.to_vec(),
+            |data| sort(black_box(data)),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn comparison_sorts(c: &mut Criterion) {
+    let mut rng = rng();
+    let parallel = sort::ParallelOptions::default();
+    for distribution in Distribution::ALL {
+        let mut group = c.benchmark_group(format!("sort/{}", distribution.name()));
+        for len in powers_of_ten(MAX_LEN) {
+            set_sample_size(&mut group, len);
+            let input = generate(distribution, len, &mut rng);
+
+            bench_sort(&mut group, "std_unstable", &input, |data| {
+                data.sort_unstable()
+            });
+            bench_sort(&mut group, "std_stable", &input, |data| data.sort());
+            bench_sort(&mut group, "quick_sort", &input, |data| {
+                sort::quick_sort(data, &mut rng)
+            });
+            bench_sort(&mut group, "intro_sort", &input, sort::intro_sort);
+            bench_sort(&mut group, "merge_sort", &input, sort::merge_sort);
+            bench_sort(
+                &mut group,
+                "stable_merge_sort",
+                &input,
+                sort::stable_merge_sort,
+            );
+            bench_sort(&mut group, "tim_sort", &input, sort::tim_sort);
+            bench_sort(&mut group, "heap_sort", &input, sort::heap_sort);
+            bench_sort(&mut group, "shell_sort", &input, |data| {
+                sort::shell_sort(data, sort::ShellGaps::default())
+            });
+            bench_sort(&mut group, "lsd_radix_sort", &input, sort::lsd_radix_sort);
+            bench_sort(&mut group, "par_quick_sort", &input, |data| {
+                sort::par_quick_sort(data, &parallel)
+            });
+            bench_sort(&mut group, "par_merge_sort", &input, |data| {
+                sort::par_merge_sort(data, &parallel)
+            });
+
+            if len <= QUADRATIC_MAX_LEN {
+                bench_sort(&mut group, "selection_sort", &input, sort::selection_sort);
+                bench_sort(&mut group, "insertion_sort", &input, sort::insertion_sort);
+                bench_sort(
+                    &mut group,
+                    "binary_insertion_sort",
+                    &input,
+                    sort::binary_insertion_sort,
+                );
+            }
+        }
+        group.finish();
+    }
+}
+
+fn special_purpose_sorts(c: &mut Criterion) {
+    let mut rng = rng();
+    let mut group = c.benchmark_group("sort/special_purpose");
+    for len in powers_of_ten(MAX_LEN) {
+        set_sample_size(&mut group, len);
+
+        let bytes: Vec<u8> = generate(Distribution::Random, len, &mut rng)
+            .into_iter()
+            .map(|value| value as u8)
+            .collect();
+        bench_sort(&mut group, "counting_sort", &bytes, sort::counting_sort);
+
+        let floats: Vec<f64> = generate(Distribution::Random, len, &mut rng)
+            .into_iter()
+            .map(|value| value as f64 / len as f64)
+            .collect();
+        bench_sort(&mut group, "bucket_sort", &floats, sort::bucket_sort);
+
+        let strings: Vec<String> = (0..len)
+            .map(|_| random_string(12, &['a', 'c', 'g', 't'], &mut rng))
+            .collect();
+        bench_sort(&mut group, "msd_radix_sort", &strings, sort::msd_radix_sort);
+        bench_sort(&mut group, "std_unstable_strings", &strings, |data| {
+            data.sort_unstable()
+        });
+    }
+    group.finish();
+}
+
+fn selection(c: &mut Criterion) {
+    let mut rng = rng();
+    let mut group = c.benchmark_group("sort/selection");
+    for len in powers_of_ten(MAX_LEN) {
+        set_sample_size(&mut group, len);
+        let input = generate(Distribution::Random, len, &mut rng);
+        let k = (len / 100).max(1);
+
+        bench_sort(&mut group, "nth_element", &input, |data| {
+            sort::nth_element(data, len / 2)
+        });
+        bench_sort(&mut group, "partial_sort", &input, |data| {
+            sort::partial_sort(data, k)
+        });
+        group.bench_with_input(BenchmarkId::new("top_k", len), &input, |b, input| {
+            b.iter(|| sort::top_k(black_box(input).iter(), k))
+        });
+    }
+    group.finish();
+}
+
+fn merging(c: &mut Criterion) {
+    let mut rng = rng();
+    let mut group = c.benchmark_group("sort/merge");
+    for len in powers_of_ten(MAX_LEN) {
+        set_sample_size(&mut group, len);
+        let mut input = generate(Distribution::Random, len, &mut rng);
+        let (lhs, rhs) = input.split_at_mut(len / 2);
+        lhs.sort_unstable();
+        rhs.sort_unstable();
+        group.bench_with_input(BenchmarkId::new("merge", len), &input, |b, input| {
+            b.iter(|| {
+                let (lhs, rhs) = black_box(input).split_at(len / 2);
+                sort::merge(lhs, rhs).count()
+            })
+        });
+
+        let sorted_runs: Vec<Vec<i32>> = input
+            .chunks(len.div_ceil(16))
+            .map(|run| {
+                let mut run = run.to_vec();
+                run.sort_unstable();
+                run
+            })
+            .collect();
+        group.bench_with_input(
+            BenchmarkId::new("k_way_merge_16", len),
+            &sorted_runs,
+            |b, runs| b.iter(|| sort::KWayMerge::new(black_box(runs).iter()).count()),
+        );
+    }
+    group.finish();
+}
+
+fn external(c: &mut Criterion) {
+    let mut rng = rng();
+    let mut group = c.benchmark_group("sort/external");
+    // Every iteration goes through the file system, so a million lines is plenty
+    for len in powers_of_ten(1_000_000) {
+        set_sample_size(&mut group, len);
+        let input: String = (0..len)
+            .map(|_| random_string(16, &['a', 'b', 'c', 'd'], &mut rng) + "\n")
+            .collect();
+        let options = sort::ExternalSortOptions {
+            // Spill about 16 runs
+            memory_budget: (input.len() / 16).max(1),
+            ..Default::default()
+        };
+        group.bench_with_input(BenchmarkId::new("lines", len), &input, |b, input| {
+            b.iter(|| {
+                let mut output = Vec::with_capacity(input.len());
+                sort::external_sort(input.as_bytes(), &mut output, &sort::LinesCodec, &options)
+                    .unwrap();
+                output
+            })
+        });
+    }
+    group.finish();
+}
+
+// The parallel sorts against the sequential ones on large random inputs, with a
+// fixed thread count so that the runs are comparable across machines
+fn parallel(c: &mut Criterion) {
+    let mut rng = rng();
+    let options = sort::ParallelOptions {
+        threads: 4,
+        ..Default::default()
+    };
+    let mut group = c.benchmark_group("sort/parallel");
+    for len in powers_of_ten(MAX_LEN).skip(4) {
+        set_sample_size(&mut group, len);
+        let input = generate(Distribution::Random, len, &mut rng);
+
+        bench_sort(&mut group, "quick_sort", &input, |data| {
+            sort::quick_sort(data, &mut rng)
+        });
+        bench_sort(&mut group, "par_quick_sort", &input, |data| {
+            sort::par_quick_sort(data, &options)
+        });
+        bench_sort(&mut group, "tim_sort", &input, sort::tim_sort);
+        bench_sort(
+            &mut group,
+            "stable_merge_sort",
+            &input,
+            sort::stable_merge_sort,
+        );
+        bench_sort(&mut group, "par_merge_sort", &input, |data| {
+            sort::par_merge_sort(data, &options)
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    comparison_sorts,
+    parallel,
+    special_purpose_sorts,
+    selection,
+    merging,
+    external
+);
+criterion_main!(benches);
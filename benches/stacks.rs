@@ -0,0 +1,63 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use cs::generators::{Distribution, generate, powers_of_ten};
+use cs::stack_with_constant_max::StackWithConstantMax;
+use cs::two_stack_queue::TwoStackQueue;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::hint::black_box;
+
+const MAX_LEN: usize = 10_000_000;
+
+// Pushes the whole input, asking for the maximum after every push, then pops
+// everything back
+fn push_pop_max(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    for distribution in [
+        Distribution::Random,
+        Distribution::Sorted,
+        Distribution::Reversed,
+    ] {
+        let mut group = c.benchmark_group(format!("stacks/{}", distribution.name()));
+        for len in powers_of_ten(MAX_LEN) {
+            group.sample_size(if len >= 1_000_000 { 10 } else { 100 });
+            let input = generate(distribution, len, &mut rng);
+
+            group.bench_with_input(
+                BenchmarkId::new("stack_with_constant_max", len),
+                &input,
+                |b, input| {
+                    b.iter(|| {
+                        let mut stack = StackWithConstantMax::new();
+                        for &value in input {
+                            stack.push(value);
+                            black_box(stack.max());
+                        }
+                        while let Some(value) = stack.pop() {
+                            black_box(value);
+                        }
+                    })
+                },
+            );
+            group.bench_with_input(
+                BenchmarkId::new("two_stack_queue", len),
+                &input,
+                |b, input| {
+                    b.iter(|| {
+                        let mut queue = TwoStackQueue::new();
+                        for &value in input {
+                            queue.push(value);
+                            black_box(queue.max());
+                        }
+                        while let Some(value) = queue.pop() {
+                            black_box(value);
+                        }
+                    })
+                },
+            );
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, push_pop_max);
+criterion_main!(benches);
@@ -1,24 +1,97 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+
 pub type Input<'a, T> = &'a [T];
 pub type Output = Option<usize>;
 
-pub fn lower_bound<T: Ord>(input: Input<T>, target: T) -> Output {
-    if input.is_empty() {
-        return None;
-    }
-
+// Returns the index of the first element for which `predicate` is false,
+// assuming the input is partitioned so that all elements satisfying it come first.
+// Complexity:
+// Time: O(log n)
+// Space: O(1)
+pub fn partition_point<T, P: FnMut(&T) -> bool>(input: Input<T>, mut predicate: P) -> usize {
     let mut left = 0;
     let mut right = input.len();
 
     while left < right {
         let mid = left + (right - left) / 2;
 
-        if input[mid] < target {
+        if predicate(&input[mid]) {
             left = mid + 1;
         } else {
             right = mid;
         }
     }
 
+    left
+}
+
+// Index of the first element that is not less than `target`.
+pub fn lower_bound<T: Ord>(input: Input<T>, target: &T) -> usize {
+    lower_bound_by(input, target, &mut T::cmp)
+}
+
+pub fn lower_bound_by<T, F>(input: Input<T>, target: &T, compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    partition_point(input, |value| compare(value, target) == Ordering::Less)
+}
+
+pub fn lower_bound_by_key<T, K: Ord, F>(input: Input<T>, key: &K, f: &mut F) -> usize
+where
+    F: FnMut(&T) -> K,
+{
+    partition_point(input, |value| f(value) < *key)
+}
+
+// Index of the first element that is greater than `target`.
+pub fn upper_bound<T: Ord>(input: Input<T>, target: &T) -> usize {
+    upper_bound_by(input, target, &mut T::cmp)
+}
+
+pub fn upper_bound_by<T, F>(input: Input<T>, target: &T, compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    partition_point(input, |value| compare(value, target) != Ordering::Greater)
+}
+
+pub fn upper_bound_by_key<T, K: Ord, F>(input: Input<T>, key: &K, f: &mut F) -> usize
+where
+    F: FnMut(&T) -> K,
+{
+    partition_point(input, |value| f(value) <= *key)
+}
+
+// Range of the elements equal to `target`, empty at the insertion point if there are none.
+pub fn equal_range<T: Ord>(input: Input<T>, target: &T) -> Range<usize> {
+    equal_range_by(input, target, &mut T::cmp)
+}
+
+pub fn equal_range_by<T, F>(input: Input<T>, target: &T, compare: &mut F) -> Range<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    lower_bound_by(input, target, compare)..upper_bound_by(input, target, compare)
+}
+
+pub fn equal_range_by_key<T, K: Ord, F>(input: Input<T>, key: &K, f: &mut F) -> Range<usize>
+where
+    F: FnMut(&T) -> K,
+{
+    lower_bound_by_key(input, key, f)..upper_bound_by_key(input, key, f)
+}
+
+// The original lower bound, which reports `None` when `target` would be
+// inserted before the first element (and for the empty input).
+pub fn lower_bound_or_none<T: Ord>(input: Input<T>, target: T) -> Output {
+    if input.is_empty() {
+        return None;
+    }
+
+    let left = lower_bound(input, &target);
+
     if left == 0 && input[left] > target {
         return None;
     }
@@ -28,31 +101,120 @@ pub fn lower_bound<T: Ord>(input: Input<T>, target: T) -> Output {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::Rng;
+
+    fn random_sorted_input<R: Rng>(rng: &mut R) -> Vec<i32> {
+        let len = rng.random_range(0..50);
+        let mut input: Vec<i32> = (0..len).map(|_| rng.random_range(0..20)).collect();
+        input.sort();
+        input
+    }
 
     #[test]
     fn test_lower_bound() {
         let input = [1, 2, 2, 3, 4, 5];
-        assert_eq!(lower_bound(&input, 1), Some(0));
-        assert_eq!(lower_bound(&input, 2), Some(1));
-        assert_eq!(lower_bound(&input, 3), Some(3));
-        assert_eq!(lower_bound(&input, 6), Some(6));
-        assert_eq!(lower_bound(&input, 0), None);
+        assert_eq!(lower_bound(&input, &0), 0);
+        assert_eq!(lower_bound(&input, &1), 0);
+        assert_eq!(lower_bound(&input, &2), 1);
+        assert_eq!(lower_bound(&input, &3), 3);
+        assert_eq!(lower_bound(&input, &6), 6);
+
+        let input: [i32; 0] = [];
+        assert_eq!(lower_bound(&input, &1), 0);
+    }
+
+    #[test]
+    fn test_upper_bound() {
+        let input = [1, 2, 2, 3, 4, 5];
+        assert_eq!(upper_bound(&input, &0), 0);
+        assert_eq!(upper_bound(&input, &1), 1);
+        assert_eq!(upper_bound(&input, &2), 3);
+        assert_eq!(upper_bound(&input, &5), 6);
+
+        let input: [i32; 0] = [];
+        assert_eq!(upper_bound(&input, &1), 0);
+    }
+
+    #[test]
+    fn test_equal_range() {
+        let input = [1, 2, 2, 3, 4, 5];
+        assert_eq!(equal_range(&input, &2), 1..3);
+        assert_eq!(equal_range(&input, &4), 4..5);
+        assert_eq!(equal_range(&input, &0), 0..0);
+        assert_eq!(equal_range(&input, &6), 6..6);
+    }
+
+    #[test]
+    fn test_partition_point() {
+        let input = [1, 3, 5, 7, 9, 2, 4];
+        assert_eq!(partition_point(&input, |value| value % 2 == 1), 5);
+        assert_eq!(partition_point(&input, |_| true), input.len());
+        assert_eq!(partition_point(&input, |_| false), 0);
+    }
+
+    #[test]
+    fn test_against_linear_scan() {
+        let mut rng = rand::rng();
+        for _ in 0..500 {
+            let input = random_sorted_input(&mut rng);
+            for target in -1..=21 {
+                let lower = input.iter().take_while(|&&value| value < target).count();
+                let upper = input.iter().take_while(|&&value| value <= target).count();
+                assert_eq!(lower_bound(&input, &target), lower);
+                assert_eq!(upper_bound(&input, &target), upper);
+                assert_eq!(equal_range(&input, &target), lower..upper);
+                assert_eq!(
+                    partition_point(&input, |&value| value < target),
+                    input.partition_point(|&value| value < target)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_by_and_by_key() {
+        let descending = [9, 7, 7, 5, 3, 1];
+        let mut reversed = |lhs: &i32, rhs: &i32| rhs.cmp(lhs);
+        assert_eq!(lower_bound_by(&descending, &7, &mut reversed), 1);
+        assert_eq!(upper_bound_by(&descending, &7, &mut reversed), 3);
+        assert_eq!(equal_range_by(&descending, &4, &mut reversed), 4..4);
+
+        let floats = [-1.5, 0.0, 0.0, 2.5, f64::INFINITY];
+        let mut total_cmp = |lhs: &f64, rhs: &f64| lhs.total_cmp(rhs);
+        assert_eq!(equal_range_by(&floats, &0.0, &mut total_cmp), 1..3);
+        assert_eq!(lower_bound_by(&floats, &3.0, &mut total_cmp), 4);
+
+        let records = [("b", 1), ("a", 2), ("d", 2), ("c", 5)];
+        let mut second = |record: &(&str, i32)| record.1;
+        assert_eq!(lower_bound_by_key(&records, &2, &mut second), 1);
+        assert_eq!(upper_bound_by_key(&records, &2, &mut second), 3);
+        assert_eq!(equal_range_by_key(&records, &3, &mut second), 3..3);
+    }
+
+    #[test]
+    fn test_lower_bound_or_none() {
+        let input = [1, 2, 2, 3, 4, 5];
+        assert_eq!(lower_bound_or_none(&input, 1), Some(0));
+        assert_eq!(lower_bound_or_none(&input, 2), Some(1));
+        assert_eq!(lower_bound_or_none(&input, 3), Some(3));
+        assert_eq!(lower_bound_or_none(&input, 6), Some(6));
+        assert_eq!(lower_bound_or_none(&input, 0), None);
 
         let input = [1, 3, 5, 7, 9];
-        assert_eq!(lower_bound(&input, 2), Some(1));
-        assert_eq!(lower_bound(&input, 4), Some(2));
-        assert_eq!(lower_bound(&input, 8), Some(4));
-        assert_eq!(lower_bound(&input, 10), Some(5));
+        assert_eq!(lower_bound_or_none(&input, 2), Some(1));
+        assert_eq!(lower_bound_or_none(&input, 4), Some(2));
+        assert_eq!(lower_bound_or_none(&input, 8), Some(4));
+        assert_eq!(lower_bound_or_none(&input, 10), Some(5));
 
         let input = [2];
-        assert_eq!(lower_bound(&input, 1), None);
-        assert_eq!(lower_bound(&input, 2), Some(0));
-        assert_eq!(lower_bound(&input, 3), Some(1));
+        assert_eq!(lower_bound_or_none(&input, 1), None);
+        assert_eq!(lower_bound_or_none(&input, 2), Some(0));
+        assert_eq!(lower_bound_or_none(&input, 3), Some(1));
     }
 
     #[test]
     fn test_empty_input() {
         let input: [i32; 0] = [];
-        assert_eq!(lower_bound(&input, 1), None);
+        assert_eq!(lower_bound_or_none(&input, 1), None);
     }
 }
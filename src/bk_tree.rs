@@ -0,0 +1,238 @@
+use crate::levenshtein_distance;
+use std::collections::BinaryHeap;
+
+// Burkhard-Keller tree: an index of words under a metric (a distance that is
+// symmetric, zero only between equal words and obeys the triangle inequality).
+// Every child of a node sits on an edge labelled with its distance d(node, child)
+// and no two children share a label. By the triangle inequality, a word within
+// k of the query q can only be under the edge labelled e if
+// |d(q, node) - e| <= k, so a search skips the subtrees outside that range.
+// Complexities:
+// insert: O(h) distance computations for a tree of height h
+// find_within, nearest: O(n) distance computations in the worst case, far
+// fewer in practice for small k
+// Space: O(n) for n words
+pub struct BkTree<D = fn(&str, &str) -> usize> {
+    // The root is nodes[0]
+    nodes: Vec<Node>,
+    distance: D,
+}
+
+struct Node {
+    word: String,
+    // (distance to this node, child index in nodes)
+    children: Vec<(usize, usize)>,
+}
+
+impl BkTree {
+    // A tree under the Levenshtein distance in chars
+    pub fn new() -> Self {
+        BkTree::with_distance(levenshtein_distance::calculate)
+    }
+}
+
+impl Default for BkTree {
+    fn default() -> Self {
+        BkTree::new()
+    }
+}
+
+impl<D: Fn(&str, &str) -> usize> BkTree<D> {
+    // `distance` has to be a metric, or searches miss words
+    pub fn with_distance(distance: D) -> Self {
+        BkTree {
+            nodes: Vec::new(),
+            distance,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // Returns false, leaving the tree unchanged, if the word is already there
+    pub fn insert(&mut self, word: &str) -> bool {
+        let new_node = Node {
+            word: word.to_string(),
+            children: Vec::new(),
+        };
+        if self.nodes.is_empty() {
+            self.nodes.push(new_node);
+            return true;
+        }
+
+        let mut current = 0;
+        loop {
+            let distance = (self.distance)(&self.nodes[current].word, word);
+            if distance == 0 {
+                return false;
+            }
+            let child = self.nodes[current]
+                .children
+                .iter()
+                .find(|&&(edge, _)| edge == distance);
+            match child {
+                Some(&(_, child)) => current = child,
+                None => {
+                    let index = self.nodes.len();
+                    self.nodes[current].children.push((distance, index));
+                    self.nodes.push(new_node);
+                    return true;
+                }
+            }
+        }
+    }
+
+    // All the words within `k` of the query with their distances, by increasing
+    // distance and then alphabetically
+    pub fn find_within(&self, query: &str, k: usize) -> Vec<(&str, usize)> {
+        let mut found = Vec::new();
+        let mut stack: Vec<usize> = if self.nodes.is_empty() {
+            Vec::new()
+        } else {
+            vec![0]
+        };
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let distance = (self.distance)(query, &node.word);
+            if distance <= k {
+                found.push((node.word.as_str(), distance));
+            }
+            stack.extend(
+                node.children
+                    .iter()
+                    .filter(|&&(edge, _)| edge.abs_diff(distance) <= k)
+                    .map(|&(_, child)| child),
+            );
+        }
+        found.sort_by(|lhs, rhs| lhs.1.cmp(&rhs.1).then(lhs.0.cmp(rhs.0)));
+        found
+    }
+
+    // The `n` words closest to the query with their distances, ordered as in
+    // `find_within`. The search radius shrinks to the distance of the n-th best
+    // word found so far.
+    pub fn nearest(&self, query: &str, n: usize) -> Vec<(&str, usize)> {
+        if n == 0 || self.nodes.is_empty() {
+            return Vec::new();
+        }
+
+        // The n best so far, worst on top
+        let mut best: BinaryHeap<(usize, &str)> = BinaryHeap::with_capacity(n + 1);
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let distance = (self.distance)(query, &node.word);
+            best.push((distance, &node.word));
+            if best.len() > n {
+                best.pop();
+            }
+
+            // A child at exactly the radius may still hold a word that wins the
+            // alphabetical tie
+            let radius = if best.len() == n {
+                best.peek().map_or(usize::MAX, |&(worst, _)| worst)
+            } else {
+                usize::MAX
+            };
+            stack.extend(
+                node.children
+                    .iter()
+                    .filter(|&&(edge, _)| edge.abs_diff(distance) <= radius)
+                    .map(|&(_, child)| child),
+            );
+        }
+        best.into_sorted_vec()
+            .into_iter()
+            .map(|(distance, word)| (word, distance))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::random_string;
+    use crate::string_distance;
+    use rand::Rng;
+
+    fn brute_force<'a>(
+        words: &'a [String],
+        query: &str,
+        distance: impl Fn(&str, &str) -> usize,
+    ) -> Vec<(&'a str, usize)> {
+        let mut all: Vec<(&str, usize)> = words
+            .iter()
+            .map(|word| (word.as_str(), distance(query, word)))
+            .collect();
+        all.sort_by(|lhs, rhs| lhs.1.cmp(&rhs.1).then(lhs.0.cmp(rhs.0)));
+        all.dedup();
+        all
+    }
+
+    #[test]
+    fn test_bk_tree() {
+        let mut tree = BkTree::new();
+        assert!(tree.is_empty());
+        assert_eq!(tree.find_within("book", 2), []);
+        assert_eq!(tree.nearest("book", 2), []);
+
+        for word in [
+            "book", "books", "cake", "boo", "boon", "cook", "cape", "cart",
+        ] {
+            assert!(tree.insert(word));
+        }
+        assert!(!tree.insert("cake"));
+        assert_eq!(tree.len(), 8);
+
+        assert_eq!(
+            tree.find_within("bo", 2),
+            [("boo", 1), ("book", 2), ("boon", 2)]
+        );
+        assert_eq!(tree.find_within("caqe", 0), []);
+        assert_eq!(tree.find_within("caqe", 1), [("cake", 1), ("cape", 1)]);
+        assert_eq!(
+            tree.nearest("boko", 3),
+            [("boo", 1), ("book", 2), ("books", 2)]
+        );
+        assert_eq!(tree.nearest("cart", 1), [("cart", 0)]);
+        assert_eq!(tree.nearest("cart", 100).len(), 8);
+        assert_eq!(tree.nearest("cart", 0), []);
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = rand::rng();
+        let alphabet = ['a', 'b', 'c', 'd', 'é'];
+        let distances: [fn(&str, &str) -> usize; 2] = [
+            levenshtein_distance::calculate,
+            string_distance::damerau_levenshtein,
+        ];
+        for distance in distances {
+            let words: Vec<String> = (0..500)
+                .map(|_| random_string(rng.random_range(0..8), &alphabet, &mut rng))
+                .collect();
+            let mut tree = BkTree::with_distance(distance);
+            for word in &words {
+                tree.insert(word);
+            }
+
+            for _ in 0..50 {
+                let query = random_string(rng.random_range(0..8), &alphabet, &mut rng);
+                let all = brute_force(&words, &query, distance);
+                assert_eq!(tree.len(), all.len());
+                for k in 0..4 {
+                    let within: Vec<_> = all.iter().copied().filter(|&(_, d)| d <= k).collect();
+                    assert_eq!(tree.find_within(&query, k), within, "{query} {k}");
+                }
+                for n in [1, 5, 20] {
+                    assert_eq!(tree.nearest(&query, n), all[..n], "{query} {n}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::BTreeMap;
+
+// A trie of words searched by Levenshtein distance in chars, like
+// `levenshtein_distance::calculate`. A search walks the trie carrying the row
+// of the DP table between the query and the prefix spelled by the path: the
+// row of a child is computed from its parent's, so a prefix shared by many
+// words is only computed once. Since no value in a row is smaller than the
+// minimum of its parent's, a subtree is skipped as soon as its row minimum
+// exceeds the maximum distance.
+// Complexities:
+// insert: O(w) for a word of w chars
+// find_within, find_prefix_within: O(m) for each trie node visited with a
+// query of m chars, at most O(m * N) for N nodes but usually far fewer
+// Space: O(N) for the trie and its words, plus O(m * d) for the rows along a
+// path of depth d
+pub struct FuzzyTrie {
+    // The root is nodes[0]
+    nodes: Vec<Node>,
+    len: usize,
+}
+
+#[derive(Default)]
+struct Node {
+    // In char order, so that walks visit words alphabetically
+    children: BTreeMap<char, usize>,
+    // The word spelled by the path to this node, if it was inserted
+    word: Option<String>,
+}
+
+impl Default for FuzzyTrie {
+    fn default() -> Self {
+        FuzzyTrie::new()
+    }
+}
+
+impl FuzzyTrie {
+    pub fn new() -> Self {
+        FuzzyTrie {
+            nodes: vec![Node::default()],
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Returns false if the word is already there
+    pub fn insert(&mut self, word: &str) -> bool {
+        let mut current = 0;
+        for c in word.chars() {
+            current = match self.nodes[current].children.get(&c) {
+                Some(&child) => child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::default());
+                    self.nodes[current].children.insert(c, child);
+                    child
+                }
+            };
+        }
+        let node = &mut self.nodes[current];
+        if node.word.is_some() {
+            return false;
+        }
+        node.word = Some(word.to_string());
+        self.len += 1;
+        true
+    }
+
+    // All the words within `k` of the query with their distances, by increasing
+    // distance and then alphabetically
+    pub fn find_within(&self, query: &str, k: usize) -> Vec<(&str, usize)> {
+        let mut search = Search::new(self, query, k);
+        search.words_within();
+        search.into_found()
+    }
+
+    // All the words with a prefix within `k` of the query, with the distance of
+    // their closest prefix, ordered as in `find_within`. This is autocomplete
+    // with typos: "progarm" finds "programming" at distance 2.
+    pub fn find_prefix_within(&self, query: &str, k: usize) -> Vec<(&str, usize)> {
+        let mut search = Search::new(self, query, k);
+        search.prefixes_within();
+        search.into_found()
+    }
+}
+
+fn sort_by_distance(found: &mut [(&str, usize)]) {
+    found.sort_by(|lhs, rhs| lhs.1.cmp(&rhs.1).then_with(|| lhs.0.cmp(rhs.0)));
+}
+
+// The walks go depth first with an explicit stack, so that long words don't
+// overflow the call stack, and keep one row buffer per depth: the row of a node
+// replaces the one of its previous sibling, whose subtree is done by then.
+struct Search<'a> {
+    trie: &'a FuzzyTrie,
+    query: Vec<char>,
+    k: usize,
+    // `rows[d][j]` is the distance between the prefix spelled by the path to
+    // the node visited at depth d and the first j chars of the query
+    rows: Vec<Vec<usize>>,
+    found: Vec<(&'a str, usize)>,
+}
+
+impl<'a> Search<'a> {
+    fn new(trie: &'a FuzzyTrie, query: &str, k: usize) -> Self {
+        let query: Vec<char> = query.chars().collect();
+        // The empty prefix at the root
+        let rows = vec![(0..=query.len()).collect()];
+        Search {
+            trie,
+            query,
+            k,
+            rows,
+            found: Vec::new(),
+        }
+    }
+
+    fn into_found(mut self) -> Vec<(&'a str, usize)> {
+        sort_by_distance(&mut self.found);
+        self.found
+    }
+
+    fn words_within(&mut self) {
+        let trie = self.trie;
+        let end = self.query.len();
+        if end <= self.k {
+            self.record(&trie.nodes[0], end);
+        }
+
+        // (char on the edge from the parent, node, depth)
+        let mut stack: Vec<(char, usize, usize)> = Vec::new();
+        stack.extend(
+            trie.nodes[0]
+                .children
+                .iter()
+                .map(|(&c, &child)| (c, child, 1)),
+        );
+        while let Some((c, index, depth)) = stack.pop() {
+            // Nothing below gets back within k
+            if self.fill_row(depth, c) > self.k {
+                continue;
+            }
+            let node = &trie.nodes[index];
+            let distance = self.rows[depth][end];
+            if distance <= self.k {
+                self.record(node, distance);
+            }
+            stack.extend(
+                node.children
+                    .iter()
+                    .map(|(&c, &child)| (c, child, depth + 1)),
+            );
+        }
+    }
+
+    // As `words_within`, carrying down the smallest distance between the query
+    // and a prefix of the path
+    fn prefixes_within(&mut self) {
+        let trie = self.trie;
+        let end = self.query.len();
+        if end <= self.k {
+            self.record(&trie.nodes[0], end);
+        }
+
+        // (char on the edge from the parent, node, depth, best distance above)
+        let mut stack: Vec<(char, usize, usize, usize)> = Vec::new();
+        stack.extend(
+            trie.nodes[0]
+                .children
+                .iter()
+                .map(|(&c, &child)| (c, child, 1, end)),
+        );
+        while let Some((c, index, depth, best)) = stack.pop() {
+            let row_min = self.fill_row(depth, c);
+            let best = best.min(self.rows[depth][end]);
+            let node = &trie.nodes[index];
+            if row_min <= self.k {
+                if best <= self.k {
+                    self.record(node, best);
+                }
+                stack.extend(
+                    node.children
+                        .iter()
+                        .map(|(&c, &child)| (c, child, depth + 1, best)),
+                );
+            } else if best <= self.k {
+                // No deeper prefix gets within k, let alone below the best
+                // distance: every word below matches at that distance
+                self.all_words(index, best);
+            }
+        }
+    }
+
+    fn all_words(&mut self, index: usize, distance: usize) {
+        let trie = self.trie;
+        let mut stack = vec![index];
+        while let Some(index) = stack.pop() {
+            let node = &trie.nodes[index];
+            self.record(node, distance);
+            stack.extend(node.children.values());
+        }
+    }
+
+    fn record(&mut self, node: &'a Node, distance: usize) {
+        if let Some(word) = &node.word {
+            self.found.push((word, distance));
+        }
+    }
+
+    // Computes the row at `depth` for the path to the row above followed by
+    // `c`, and returns its minimum
+    fn fill_row(&mut self, depth: usize, c: char) -> usize {
+        if self.rows.len() == depth {
+            self.rows.push(Vec::with_capacity(self.query.len() + 1));
+        }
+        let (above, below) = self.rows.split_at_mut(depth);
+        let (row, next) = (&above[depth - 1], &mut below[0]);
+        next.clear();
+        next.push(row[0] + 1);
+        let mut min = next[0];
+        for (j, &q) in self.query.iter().enumerate() {
+            let substitution = row[j] + usize::from(q != c);
+            // Deletion of c, insertion of q
+            let value = substitution.min(row[j + 1] + 1).min(next[j] + 1);
+            next.push(value);
+            min = min.min(value);
+        }
+        min
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::random_string;
+    use crate::levenshtein_distance;
+    use rand::Rng;
+
+    #[test]
+    fn test_fuzzy_trie() {
+        let mut trie = FuzzyTrie::new();
+        assert!(trie.is_empty());
+        assert_eq!(trie.find_within("", 3), []);
+        assert_eq!(FuzzyTrie::default().find_prefix_within("a", 1), []);
+
+        for word in [
+            "program",
+            "programming",
+            "progress",
+            "project",
+            "pro",
+            "ant",
+        ] {
+            assert!(trie.insert(word));
+        }
+        assert!(!trie.insert("pro"));
+        assert_eq!(trie.len(), 6);
+
+        assert_eq!(trie.find_within("progarm", 2), [("program", 2)]);
+        assert_eq!(trie.find_within("prog", 3), [("pro", 1), ("program", 3)]);
+        assert_eq!(
+            trie.find_prefix_within("progarm", 2),
+            [("program", 2), ("programming", 2), ("progress", 2)]
+        );
+        assert_eq!(
+            trie.find_prefix_within("prg", 1),
+            [
+                ("pro", 1),
+                ("program", 1),
+                ("programming", 1),
+                ("progress", 1),
+                ("project", 1),
+            ]
+        );
+        // Every word has the empty prefix
+        assert_eq!(trie.find_prefix_within("", 0).len(), 6);
+        assert_eq!(trie.find_within("", 3), [("ant", 3), ("pro", 3)]);
+    }
+
+    #[test]
+    fn test_long_words() {
+        let long = "a".repeat(200_000);
+        let mut trie = FuzzyTrie::new();
+        trie.insert(&long);
+        trie.insert("b");
+
+        assert_eq!(
+            trie.find_within("", 200_000),
+            [("b", 1), (long.as_str(), 200_000)]
+        );
+        assert_eq!(trie.find_within("aa", 1), []);
+        assert_eq!(
+            trie.find_prefix_within("b", 1),
+            [("b", 0), (long.as_str(), 1)]
+        );
+        assert_eq!(trie.find_prefix_within("", 0).len(), 2);
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = rand::rng();
+        let alphabet = ['a', 'b', 'c', 'é'];
+        let words: Vec<String> = (0..300)
+            .map(|_| random_string(rng.random_range(0..8), &alphabet, &mut rng))
+            .collect();
+        let mut trie = FuzzyTrie::new();
+        for word in &words {
+            trie.insert(word);
+        }
+        let mut unique = words.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(trie.len(), unique.len());
+
+        for _ in 0..50 {
+            let query = random_string(rng.random_range(0..6), &alphabet, &mut rng);
+            for k in 0..4 {
+                let mut within: Vec<(&str, usize)> = unique
+                    .iter()
+                    .map(|word| (word.as_str(), levenshtein_distance::calculate(&query, word)))
+                    .filter(|&(_, distance)| distance <= k)
+                    .collect();
+                sort_by_distance(&mut within);
+                assert_eq!(trie.find_within(&query, k), within, "{query} {k}");
+
+                let mut prefix_within: Vec<(&str, usize)> = unique
+                    .iter()
+                    .map(|word| {
+                        let closest = (0..=word.chars().count())
+                            .map(|len| {
+                                let prefix: String = word.chars().take(len).collect();
+                                levenshtein_distance::calculate(&query, &prefix)
+                            })
+                            .min()
+                            .unwrap();
+                        (word.as_str(), closest)
+                    })
+                    .filter(|&(_, distance)| distance <= k)
+                    .collect();
+                sort_by_distance(&mut prefix_within);
+                assert_eq!(
+                    trie.find_prefix_within(&query, k),
+                    prefix_within,
+                    "{query} {k}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+// Shapes of input data shared by the benchmarks and the tests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Distribution {
+    // A random permutation of 0..len
+    Random,
+    Sorted,
+    Reversed,
+    // Random values out of FEW_UNIQUE_VALUES distinct ones
+    FewUnique,
+    // Ascending up to the middle, then descending
+    OrganPipe,
+    // SAWTOOTH_TEETH ascending runs of 0, 1, 2, ...
+    Sawtooth,
+}
+
+pub const FEW_UNIQUE_VALUES: i32 = 8;
+pub const SAWTOOTH_TEETH: usize = 16;
+
+impl Distribution {
+    pub const ALL: [Distribution; 6] = [
+        Distribution::Random,
+        Distribution::Sorted,
+        Distribution::Reversed,
+        Distribution::FewUnique,
+        Distribution::OrganPipe,
+        Distribution::Sawtooth,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Distribution::Random => "random",
+            Distribution::Sorted => "sorted",
+            Distribution::Reversed => "reversed",
+            Distribution::FewUnique => "few_unique",
+            Distribution::OrganPipe => "organ_pipe",
+            Distribution::Sawtooth => "sawtooth",
+        }
+    }
+}
+
+pub fn generate<R: Rng>(distribution: Distribution, len: usize, rng: &mut R) -> Vec<i32> {
+    let len_i32 = i32::try_from(len).expect("length fits in i32");
+    match distribution {
+        Distribution::Random => {
+            let mut output: Vec<i32> = (0..len_i32).collect();
+            output.shuffle(rng);
+            output
+        }
+        Distribution::Sorted => (0..len_i32).collect(),
+        Distribution::Reversed => (0..len_i32).rev().collect(),
+        Distribution::FewUnique => (0..len)
+            .map(|_| rng.random_range(0..FEW_UNIQUE_VALUES))
+            .collect(),
+        Distribution::OrganPipe => {
+            let half = len_i32 / 2;
+            (0..half).chain((0..len_i32 - half).rev()).collect()
+        }
+        Distribution::Sawtooth => {
+            let tooth = len.div_ceil(SAWTOOTH_TEETH).max(1);
+            (0..len).map(|i| (i % tooth) as i32).collect()
+        }
+    }
+}
+
+pub fn random_string<R: Rng>(len: usize, alphabet: &[char], rng: &mut R) -> String {
+    (0..len)
+        .map(|_| alphabet[rng.random_range(0..alphabet.len())])
+        .collect()
+}
+
+// Input sizes 10, 100, ... up to and including `max_len`.
+pub fn powers_of_ten(max_len: usize) -> impl Iterator<Item = usize> {
+    std::iter::successors(Some(10usize), |len| len.checked_mul(10))
+        .take_while(move |&len| len <= max_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let mut rng = rand::rng();
+        assert_eq!(generate(Distribution::Sorted, 4, &mut rng), [0, 1, 2, 3]);
+        assert_eq!(generate(Distribution::Reversed, 4, &mut rng), [3, 2, 1, 0]);
+        assert_eq!(
+            generate(Distribution::OrganPipe, 5, &mut rng),
+            [0, 1, 2, 1, 0]
+        );
+        assert_eq!(
+            generate(Distribution::Sawtooth, 40, &mut rng)[..8],
+            [0, 1, 2, 0, 1, 2, 0, 1]
+        );
+
+        let mut random = generate(Distribution::Random, 100, &mut rng);
+        random.sort();
+        assert_eq!(random, generate(Distribution::Sorted, 100, &mut rng));
+
+        let few_unique = generate(Distribution::FewUnique, 1000, &mut rng);
+        assert!(
+            few_unique
+                .iter()
+                .all(|value| (0..FEW_UNIQUE_VALUES).contains(value))
+        );
+
+        for distribution in Distribution::ALL {
+            for len in [0, 1, 2, 17] {
+                assert_eq!(generate(distribution, len, &mut rng).len(), len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_powers_of_ten() {
+        assert_eq!(powers_of_ten(5).count(), 0);
+        assert_eq!(powers_of_ten(1000).collect::<Vec<_>>(), [10, 100, 1000]);
+        assert_eq!(
+            powers_of_ten(usize::MAX).last(),
+            Some(10usize.pow(usize::MAX.ilog10()))
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 pub type Input<'a, T> = &'a [T];
 pub type Output = Vec<Option<usize>>;
 
@@ -5,12 +7,26 @@ pub type Output = Vec<Option<usize>>;
 // Time: O(n)
 // Space: O(n)
 pub fn find<T: Ord>(input: Input<T>) -> Output {
+    find_by(input, &mut T::cmp)
+}
+
+pub fn find_by_key<T, K: Ord, F>(input: Input<T>, f: &mut F) -> Output
+where
+    F: FnMut(&T) -> K,
+{
+    find_by(input, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)))
+}
+
+pub fn find_by<T, F>(input: Input<T>, compare: &mut F) -> Output
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
     let mut stack = Vec::new();
     let mut result = vec![None; input.len()];
 
     for (i, value) in input.iter().enumerate() {
         while let Some(&j) = stack.last() {
-            if input[j] < *value {
+            if compare(&input[j], value) == Ordering::Less {
                 result[i] = Some(j);
                 break;
             }
@@ -53,4 +69,20 @@ mod tests {
         ];
         assert_eq!(find(&input), expected);
     }
+
+    #[test]
+    fn test_find_by_and_by_key() {
+        // Nearest greater element is the nearest smaller one in the reversed order
+        let input = vec![3, 2, 1, 4, 5];
+        let expected = vec![None, Some(0), Some(1), None, None];
+        assert_eq!(find_by(&input, &mut |lhs, rhs| rhs.cmp(lhs)), expected);
+
+        let input = vec![1.5, f64::NAN, -0.5, 2.0];
+        let expected = vec![None, Some(0), None, Some(2)];
+        assert_eq!(find_by(&input, &mut f64::total_cmp), expected);
+
+        let input = vec![("a", 3), ("b", 1), ("c", 2)];
+        let expected = vec![None, None, Some(1)];
+        assert_eq!(find_by_key(&input, &mut |record| record.1), expected);
+    }
 }
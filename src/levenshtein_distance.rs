@@ -1,32 +1,582 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+// The distance between strings can be counted in different units: the same
+// "é" is one char, one grapheme cluster and two bytes, while "e\u{301}" (e and
+// a combining accent) renders the same but is two chars and three bytes.
+// Each of them is `distance` with `UnitCosts` over the strings split into that
+// unit, so very short strings are compared by the DP and longer ones by the
+// bit-parallel algorithm. ASCII strings are compared as bytes.
 // Complexities:
-// Time: O(m * n) where m and n are the lengths of the two strings.
-// Space: O(min(m, n)) due to the storage of two rows of the DP table.
+// Time: O(ceil(min(m, n) / 64) * max(m, n)) where m and n are the lengths of the
+// two strings in the unit, plus O(log σ) per element for σ distinct elements in
+// the shorter string unless it is ASCII.
+// Space: O(m + n) for splitting the strings into chars or graphemes.
+
+// Distance in chars (Unicode scalar values)
 pub fn calculate(lhs: &str, rhs: &str) -> usize {
-    if lhs.is_empty() {
-        return rhs.len();
+    calculate_chars(lhs, rhs)
+}
+
+pub fn calculate_chars(lhs: &str, rhs: &str) -> usize {
+    // An ASCII char is a single byte
+    if lhs.is_ascii() && rhs.is_ascii() {
+        return calculate_bytes(lhs, rhs);
+    }
+    let lhs: Vec<char> = lhs.chars().collect();
+    let rhs: Vec<char> = rhs.chars().collect();
+    distance(&lhs, &rhs, &UnitCosts)
+}
+
+// Distance in extended grapheme clusters, the characters as a reader sees them
+pub fn calculate_graphemes(lhs: &str, rhs: &str) -> usize {
+    let lhs: Vec<&str> = lhs.graphemes(true).collect();
+    let rhs: Vec<&str> = rhs.graphemes(true).collect();
+    distance(&lhs, &rhs, &UnitCosts)
+}
+
+// Distance in UTF-8 bytes. The same as `distance` with `UnitCosts` on the
+// bytes, but the masks of the bit-parallel algorithm are looked up in a table
+// indexed by byte instead of searched for.
+pub fn calculate_bytes(lhs: &str, rhs: &str) -> usize {
+    unit_distance_bytes(lhs.as_bytes(), rhs.as_bytes())
+}
+
+// The cost of each edit operation. Equal elements are always matched for free,
+// so `substitute` is only asked about different ones.
+pub trait EditCosts<T> {
+    fn insert(&self, element: &T) -> usize;
+
+    fn delete(&self, element: &T) -> usize;
+
+    fn substitute(&self, from: &T, to: &T) -> usize;
+
+    // Whether every operation costs 1, which lets `distance` use the
+    // bit-parallel algorithm. Only `UnitCosts` says so: the type of the
+    // argument can't be named outside this module, so no other implementation
+    // can override the method (or call it) and get wrong distances.
+    fn is_unit(&self, _: sealed::Token) -> bool {
+        false
+    }
+}
+
+mod sealed {
+    pub struct Token;
+}
+
+// Every operation costs 1: the Levenshtein distance
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UnitCosts;
+
+impl<T> EditCosts<T> for UnitCosts {
+    fn insert(&self, _element: &T) -> usize {
+        1
+    }
+
+    fn delete(&self, _element: &T) -> usize {
+        1
+    }
+
+    fn substitute(&self, _from: &T, _to: &T) -> usize {
+        1
+    }
+
+    fn is_unit(&self, _: sealed::Token) -> bool {
+        true
+    }
+}
+
+// A fixed cost per kind of operation, whatever the elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeightedCosts {
+    pub insert: usize,
+    pub delete: usize,
+    pub substitute: usize,
+}
+
+impl<T> EditCosts<T> for WeightedCosts {
+    fn insert(&self, _element: &T) -> usize {
+        self.insert
+    }
+
+    fn delete(&self, _element: &T) -> usize {
+        self.delete
+    }
+
+    fn substitute(&self, _from: &T, _to: &T) -> usize {
+        self.substitute
+    }
+}
+
+// The costs of turning rhs into lhs instead: inserts and deletes swap places.
+struct Inverted<'a, C>(&'a C);
+
+impl<T, C: EditCosts<T>> EditCosts<T> for Inverted<'_, C> {
+    fn insert(&self, element: &T) -> usize {
+        self.0.delete(element)
+    }
+
+    fn delete(&self, element: &T) -> usize {
+        self.0.insert(element)
+    }
+
+    fn substitute(&self, from: &T, to: &T) -> usize {
+        self.0.substitute(to, from)
     }
-    if rhs.is_empty() {
-        return lhs.len();
+}
+
+// The cheapest way to turn lhs into rhs, for sequences of anything comparable:
+// chars, tokens, DNA bases... Unit costs go through `bit_parallel_distance`
+// unless the shorter sequence is very short.
+// Complexities:
+// Time: O(m * n) calls to the costs, or as `bit_parallel_distance` for unit
+// costs
+// Space: O(min(m, n)), or as `bit_parallel_distance` for unit costs
+pub fn distance<T: Ord>(lhs: &[T], rhs: &[T], costs: &impl EditCosts<T>) -> usize {
+    if costs.is_unit(sealed::Token) {
+        return unit_distance(lhs, rhs);
     }
+    // The rows span the shorter sequence
     if lhs.len() < rhs.len() {
-        return calculate(rhs, lhs);
+        last_row(rhs.iter(), lhs.iter(), &Inverted(costs))[lhs.len()]
+    } else {
+        last_row(lhs.iter(), rhs.iter(), costs)[rhs.len()]
     }
+}
 
-    // Now lhs is guaranteed to be the longer string.
+// Unit-cost edit distance by Myers' bit-vector algorithm, in Hyyrö's
+// formulation for the edit distance. The shorter sequence is the pattern: a
+// column of the DP table over it is encoded as bit vectors of the vertical
+// differences between consecutive cells (each -1, 0 or +1), and a whole column
+// is advanced per element of the text with a handful of word operations.
+// Patterns of up to 64 elements fit in a single word, longer ones are split
+// into blocks of 64 rows which pass the horizontal difference up to each other.
+// The rows matching each text element are found by a binary search among the
+// distinct elements of the pattern.
+// Complexities:
+// Time: O((ceil(m / 64) + log σ) * n + m log m) for a pattern of length m with
+// σ distinct elements and a text of length n
+// Space: O(ceil(m / 64) * σ)
+pub fn bit_parallel_distance<T: Ord>(lhs: &[T], rhs: &[T]) -> usize {
+    let (pattern, text) = shorter_first(lhs, rhs);
+    if pattern.is_empty() {
+        return text.len();
+    }
+    pattern_distance(pattern.len(), &SortedMasks::new(pattern), text)
+}
 
-    let mut previous_row: Vec<usize> = (0..=rhs.len()).collect();
-    let mut current_row: Vec<usize> = vec![0; rhs.len() + 1];
+// Up to this pattern length the DP is faster than building the masks. From 3
+// elements on the bit-parallel algorithm is faster with the byte table and as
+// fast with the binary search, from 4 on faster with both, see the
+// levenshtein_distance/crossover benchmark.
+const DP_MAX_PATTERN: usize = 2;
 
-    for (i, lc) in lhs.chars().enumerate() {
-        current_row[0] = i + 1;
+fn unit_distance<T: Ord>(lhs: &[T], rhs: &[T]) -> usize {
+    let (pattern, text) = shorter_first(lhs, rhs);
+    if pattern.len() <= DP_MAX_PATTERN {
+        last_row(text.iter(), pattern.iter(), &UnitCosts)[pattern.len()]
+    } else {
+        pattern_distance(pattern.len(), &SortedMasks::new(pattern), text)
+    }
+}
 
-        for (j, rc) in rhs.chars().enumerate() {
-            let cost = if lc == rc { 0 } else { 1 };
+fn unit_distance_bytes(lhs: &[u8], rhs: &[u8]) -> usize {
+    let (pattern, text) = shorter_first(lhs, rhs);
+    if pattern.len() <= DP_MAX_PATTERN {
+        last_row(text.iter(), pattern.iter(), &UnitCosts)[pattern.len()]
+    } else {
+        pattern_distance(pattern.len(), &ByteMasks::new(pattern), text)
+    }
+}
+
+fn shorter_first<'a, T>(lhs: &'a [T], rhs: &'a [T]) -> (&'a [T], &'a [T]) {
+    if lhs.len() <= rhs.len() {
+        (lhs, rhs)
+    } else {
+        (rhs, lhs)
+    }
+}
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+// For every element, the rows of the pattern holding it, as one bit per row in
+// ceil(m / 64) words
+trait PatternMasks<T> {
+    fn blocks(&self) -> usize;
+
+    // None if the element is not in the pattern
+    fn get(&self, element: &T) -> Option<&[u64]>;
+}
+
+// A table indexed by the byte value
+struct ByteMasks {
+    blocks: usize,
+    masks: Vec<u64>,
+}
+
+impl ByteMasks {
+    fn new(pattern: &[u8]) -> Self {
+        let blocks = pattern.len().div_ceil(WORD_BITS);
+        let mut masks = vec![0; 256 * blocks];
+        for (row, &byte) in pattern.iter().enumerate() {
+            masks[usize::from(byte) * blocks + row / WORD_BITS] |= 1 << (row % WORD_BITS);
+        }
+        ByteMasks { blocks, masks }
+    }
+}
+
+impl PatternMasks<u8> for ByteMasks {
+    fn blocks(&self) -> usize {
+        self.blocks
+    }
+
+    fn get(&self, element: &u8) -> Option<&[u64]> {
+        let start = usize::from(*element) * self.blocks;
+        Some(&self.masks[start..start + self.blocks])
+    }
+}
+
+// The distinct elements in sorted order, found by binary search
+struct SortedMasks<'a, T> {
+    blocks: usize,
+    elements: Vec<&'a T>,
+    masks: Vec<u64>,
+}
+
+impl<'a, T: Ord> SortedMasks<'a, T> {
+    fn new(pattern: &'a [T]) -> Self {
+        let blocks = pattern.len().div_ceil(WORD_BITS);
+        let mut elements: Vec<&T> = pattern.iter().collect();
+        elements.sort_unstable();
+        elements.dedup();
+        let mut table = SortedMasks {
+            blocks,
+            masks: vec![0; elements.len() * blocks],
+            elements,
+        };
+        for (row, element) in pattern.iter().enumerate() {
+            let index = table.position(element).expect("every element is listed");
+            table.masks[index * blocks + row / WORD_BITS] |= 1 << (row % WORD_BITS);
+        }
+        table
+    }
+
+    fn position(&self, element: &T) -> Option<usize> {
+        self.elements.binary_search(&element).ok()
+    }
+}
+
+impl<T: Ord> PatternMasks<T> for SortedMasks<'_, T> {
+    fn blocks(&self) -> usize {
+        self.blocks
+    }
+
+    fn get(&self, element: &T) -> Option<&[u64]> {
+        let start = self.position(element)? * self.blocks;
+        Some(&self.masks[start..start + self.blocks])
+    }
+}
+
+// The vertical differences of a column: bit i of `positive` (`negative`) is
+// set if the cell in row i + 1 is one more (less) than the cell in row i
+#[derive(Clone, Copy)]
+struct Column {
+    positive: u64,
+    negative: u64,
+}
+
+impl Column {
+    fn new() -> Self {
+        // The first column goes 0, 1, 2, ...
+        Column {
+            positive: !0,
+            negative: 0,
+        }
+    }
+
+    // Moves the column one element of the text to the right. `matches` has the
+    // bits of the rows whose pattern element equals that text element, and
+    // `carry_in` is the horizontal difference entering at the top, in the row
+    // above the first one of the word. Returns the horizontal difference at
+    // row `out_bit`.
+    fn advance(&mut self, matches: u64, carry_in: i8, out_bit: u32) -> i8 {
+        let Column { positive, negative } = *self;
+        let mut matches = matches;
+        let vertical = matches | negative;
+        if carry_in < 0 {
+            matches |= 1;
+        }
+        let horizontal = (((matches & positive).wrapping_add(positive)) ^ positive) | matches;
+        let mut horizontal_positive = negative | !(horizontal | positive);
+        let mut horizontal_negative = positive & horizontal;
+
+        let carry_out = if horizontal_positive >> out_bit & 1 != 0 {
+            1
+        } else if horizontal_negative >> out_bit & 1 != 0 {
+            -1
+        } else {
+            0
+        };
+
+        horizontal_positive <<= 1;
+        horizontal_negative <<= 1;
+        match carry_in {
+            1 => horizontal_positive |= 1,
+            -1 => horizontal_negative |= 1,
+            _ => {}
+        }
+        self.positive = horizontal_negative | !(vertical | horizontal_positive);
+        self.negative = horizontal_positive & vertical;
+        carry_out
+    }
+}
+
+// The distance between a non-empty pattern, given by its masks, and the text
+fn pattern_distance<T>(pattern_len: usize, masks: &impl PatternMasks<T>, text: &[T]) -> usize {
+    if masks.blocks() == 1 {
+        single_word_distance(pattern_len, masks, text)
+    } else {
+        block_distance(pattern_len, masks, text)
+    }
+}
+
+fn single_word_distance<T>(pattern_len: usize, masks: &impl PatternMasks<T>, text: &[T]) -> usize {
+    let out_bit = (pattern_len - 1) as u32;
+    let mut column = Column::new();
+    let mut score = pattern_len;
+    for element in text {
+        let matches = masks.get(element).map_or(0, |masks| masks[0]);
+        // Each step of the text adds one to the top row of the table
+        match column.advance(matches, 1, out_bit) {
+            1 => score += 1,
+            -1 => score -= 1,
+            _ => {}
+        }
+    }
+    score
+}
+
+fn block_distance<T>(pattern_len: usize, masks: &impl PatternMasks<T>, text: &[T]) -> usize {
+    let blocks = masks.blocks();
+    // The rows past the end of the pattern in the last block are computed
+    // too, but differences only travel upwards, so they never affect the score
+    let last_out_bit = ((pattern_len - 1) % WORD_BITS) as u32;
+    let mut columns = vec![Column::new(); blocks];
+    let mut score = pattern_len;
+    for element in text {
+        let matches = masks.get(element);
+        let mut carry = 1;
+        for (block, column) in columns.iter_mut().enumerate() {
+            let out_bit = if block + 1 == blocks {
+                last_out_bit
+            } else {
+                WORD_BITS as u32 - 1
+            };
+            carry = column.advance(matches.map_or(0, |masks| masks[block]), carry, out_bit);
+        }
+        match carry {
+            1 => score += 1,
+            -1 => score -= 1,
+            _ => {}
+        }
+    }
+    score
+}
+
+// The distance in chars if it is at most `max`, None otherwise.
+// Only the cells within `max` of the diagonal can be on a path costing at most
+// `max` (Ukkonen's band), and the computation stops at the first row in which
+// every cell already exceeds `max`.
+// Complexities:
+// Time: O(max * max(m, n)), as each element of the longer sequence fills a band
+// of at most 2 * max + 1 cells. O(1) when the lengths differ by more than max.
+// Space: O(min(m, n))
+pub fn calculate_bounded(lhs: &str, rhs: &str, max: usize) -> Option<usize> {
+    let lhs: Vec<char> = lhs.chars().collect();
+    let rhs: Vec<char> = rhs.chars().collect();
+    bounded_distance(&lhs, &rhs, max)
+}
+
+pub fn bounded_distance<T: Eq>(lhs: &[T], rhs: &[T], max: usize) -> Option<usize> {
+    if lhs.len() < rhs.len() {
+        return bounded_distance(rhs, lhs, max);
+    }
+
+    // Now lhs is the longer sequence, the rows span rhs.
+    let (m, n) = (lhs.len(), rhs.len());
+    if m - n > max {
+        return None;
+    }
+    // No distance is above m, and it keeps max + 1 from overflowing
+    let max = max.min(m);
+    // Stands for every value above max, the cells outside of the band included
+    let beyond = max + 1;
+
+    let mut previous_row: Vec<usize> = (0..=n).map(|j| j.min(beyond)).collect();
+    let mut current_row = vec![beyond; n + 1];
+
+    for i in 1..=m {
+        let start = i.saturating_sub(max).max(1);
+        let end = (i + max).min(n);
+
+        current_row[0] = i.min(beyond);
+        // The cell left of the band, set in an earlier row
+        if start > 1 {
+            current_row[start - 1] = beyond;
+        }
+        let mut row_min = current_row[start - 1];
+
+        for j in start..=end {
+            let cost = usize::from(lhs[i - 1] != rhs[j - 1]);
+            current_row[j] = (previous_row[j - 1] + cost)
+                .min(previous_row[j] + 1)
+                .min(current_row[j - 1] + 1)
+                .min(beyond);
+            row_min = row_min.min(current_row[j]);
+        }
+        if end < n {
+            current_row[end + 1] = beyond;
+        }
+
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+
+    Some(previous_row[n]).filter(|&distance| distance <= max)
+}
+
+// One step of an edit script, with the positions of the elements (chars for
+// strings) it refers to in lhs and rhs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edit {
+    Match { lhs: usize, rhs: usize },
+    Substitute { lhs: usize, rhs: usize },
+    // rhs[rhs] is inserted
+    Insert { rhs: usize },
+    // lhs[lhs] is deleted
+    Delete { lhs: usize },
+}
+
+impl Edit {
+    fn inverted(self) -> Edit {
+        match self {
+            Edit::Match { lhs, rhs } => Edit::Match { lhs: rhs, rhs: lhs },
+            Edit::Substitute { lhs, rhs } => Edit::Substitute { lhs: rhs, rhs: lhs },
+            Edit::Insert { rhs } => Edit::Delete { lhs: rhs },
+            Edit::Delete { lhs } => Edit::Insert { rhs: lhs },
+        }
+    }
+}
+
+// A shortest sequence of edits turning lhs into rhs, ordered by position.
+// Hirschberg's algorithm: the middle row of lhs is matched to its place in rhs
+// with one DP pass from the front and one from the back, then both halves are
+// solved recursively, so the table is never stored.
+// Complexity:
+// Time: O(m * n)
+// Space: O(min(m, n)) besides the script, plus O(log max(m, n)) of recursion
+pub fn edit_script(lhs: &str, rhs: &str) -> Vec<Edit> {
+    let lhs: Vec<char> = lhs.chars().collect();
+    let rhs: Vec<char> = rhs.chars().collect();
+    edit_script_of(&lhs, &rhs)
+}
+
+pub fn edit_script_of<T: PartialEq>(lhs: &[T], rhs: &[T]) -> Vec<Edit> {
+    let mut script = Vec::with_capacity(lhs.len().max(rhs.len()));
+    // The rows span the shorter sequence, the script is turned around after
+    if lhs.len() < rhs.len() {
+        hirschberg(rhs, lhs, (0, 0), &mut script);
+        script.iter_mut().for_each(|edit| *edit = edit.inverted());
+    } else {
+        hirschberg(lhs, rhs, (0, 0), &mut script);
+    }
+    script
+}
+
+fn hirschberg<T: PartialEq>(
+    lhs: &[T],
+    rhs: &[T],
+    (lhs_offset, rhs_offset): (usize, usize),
+    script: &mut Vec<Edit>,
+) {
+    let inserts = |range: std::ops::Range<usize>| {
+        range.map(move |j| Edit::Insert {
+            rhs: rhs_offset + j,
+        })
+    };
+
+    match lhs.len() {
+        0 => script.extend(inserts(0..rhs.len())),
+        1 => match rhs.iter().position(|element| *element == lhs[0]) {
+            Some(j) => {
+                script.extend(inserts(0..j));
+                script.push(Edit::Match {
+                    lhs: lhs_offset,
+                    rhs: rhs_offset + j,
+                });
+                script.extend(inserts(j + 1..rhs.len()));
+            }
+            None if rhs.is_empty() => script.push(Edit::Delete { lhs: lhs_offset }),
+            None => {
+                script.push(Edit::Substitute {
+                    lhs: lhs_offset,
+                    rhs: rhs_offset,
+                });
+                script.extend(inserts(1..rhs.len()));
+            }
+        },
+        len => {
+            let mid = len / 2;
+            let forward = last_row(lhs[..mid].iter(), rhs.iter(), &UnitCosts);
+            let backward = last_row(lhs[mid..].iter().rev(), rhs.iter().rev(), &UnitCosts);
+            // Where the best path crosses from row `mid - 1` to row `mid`
+            let split = (0..=rhs.len())
+                .min_by_key(|&j| forward[j] + backward[rhs.len() - j])
+                .unwrap_or(0);
+
+            hirschberg(&lhs[..mid], &rhs[..split], (lhs_offset, rhs_offset), script);
+            hirschberg(
+                &lhs[mid..],
+                &rhs[split..],
+                (lhs_offset + mid, rhs_offset + split),
+                script,
+            );
+        }
+    }
+}
+
+// The last row of the DP table of lhs against rhs: row[j] is the distance
+// between the whole of lhs and the first j elements of rhs.
+fn last_row<'a, T, C, L, R>(lhs: L, rhs: R, costs: &C) -> Vec<usize>
+where
+    T: PartialEq + 'a,
+    C: EditCosts<T>,
+    L: Iterator<Item = &'a T>,
+    R: Iterator<Item = &'a T> + Clone,
+{
+    let mut previous_row: Vec<usize> = std::iter::once(0)
+        .chain(rhs.clone().scan(0, |inserted, rc| {
+            *inserted += costs.insert(rc);
+            Some(*inserted)
+        }))
+        .collect();
+    let mut current_row = vec![0; previous_row.len()];
+
+    for lc in lhs {
+        current_row[0] = previous_row[0] + costs.delete(lc);
+
+        for (j, rc) in rhs.clone().enumerate() {
+            let cost = if lc == rc {
+                0
+            } else {
+                costs.substitute(lc, rc)
+            };
 
             current_row[j + 1] = std::cmp::min(
                 std::cmp::min(
-                    current_row[j] + 1,      // Insertion
-                    previous_row[j + 1] + 1, // Deletion
+                    current_row[j] + costs.insert(rc),      // Insertion
+                    previous_row[j + 1] + costs.delete(lc), // Deletion
                 ),
                 previous_row[j] + cost, // Substitution
             );
@@ -35,12 +585,90 @@ pub fn calculate(lhs: &str, rhs: &str) -> usize {
         std::mem::swap(&mut previous_row, &mut current_row);
     }
 
-    previous_row[rhs.len()]
+    previous_row
+}
+
+// Two rows of text with the aligned characters of lhs and rhs one above the
+// other, and GAP where a character was inserted or deleted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alignment {
+    pub lhs: String,
+    pub rhs: String,
+}
+
+pub const GAP: char = '-';
+
+impl std::fmt::Display for Alignment {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}\n{}", self.lhs, self.rhs)
+    }
+}
+
+pub fn align(lhs: &str, rhs: &str) -> Alignment {
+    let lhs: Vec<char> = lhs.chars().collect();
+    let rhs: Vec<char> = rhs.chars().collect();
+
+    let mut alignment = Alignment {
+        lhs: String::new(),
+        rhs: String::new(),
+    };
+    for edit in edit_script_of(&lhs, &rhs) {
+        let (top, bottom) = match edit {
+            Edit::Match { lhs: i, rhs: j } | Edit::Substitute { lhs: i, rhs: j } => {
+                (lhs[i], rhs[j])
+            }
+            Edit::Insert { rhs: j } => (GAP, rhs[j]),
+            Edit::Delete { lhs: i } => (lhs[i], GAP),
+        };
+        alignment.lhs.push(top);
+        alignment.rhs.push(bottom);
+    }
+    alignment
 }
 
 #[cfg(test)]
 mod tests {
-    use super::calculate;
+    use super::*;
+    use rand::Rng;
+
+    // Applies the script to lhs, checking that it is consistent with both
+    // sides, and returns its cost
+    fn check_script(lhs: &str, rhs: &str, script: &[Edit]) -> usize {
+        let lhs: Vec<char> = lhs.chars().collect();
+        let rhs: Vec<char> = rhs.chars().collect();
+        let (mut i, mut j) = (0, 0);
+        let mut cost = 0;
+        for edit in script {
+            match *edit {
+                Edit::Match {
+                    lhs: at_lhs,
+                    rhs: at_rhs,
+                } => {
+                    assert_eq!((at_lhs, at_rhs), (i, j));
+                    assert_eq!(lhs[i], rhs[j]);
+                    (i, j) = (i + 1, j + 1);
+                }
+                Edit::Substitute {
+                    lhs: at_lhs,
+                    rhs: at_rhs,
+                } => {
+                    assert_eq!((at_lhs, at_rhs), (i, j));
+                    assert_ne!(lhs[i], rhs[j]);
+                    (i, j, cost) = (i + 1, j + 1, cost + 1);
+                }
+                Edit::Insert { rhs: at_rhs } => {
+                    assert_eq!(at_rhs, j);
+                    (j, cost) = (j + 1, cost + 1);
+                }
+                Edit::Delete { lhs: at_lhs } => {
+                    assert_eq!(at_lhs, i);
+                    (i, cost) = (i + 1, cost + 1);
+                }
+            }
+        }
+        assert_eq!((i, j), (lhs.len(), rhs.len()));
+        cost
+    }
 
     #[test]
     fn test_identical_strings() {
@@ -79,4 +707,309 @@ mod tests {
     fn test_case_sensitivity() {
         assert_eq!(calculate("abc", "ABC"), 3);
     }
+
+    #[test]
+    fn test_edit_script() {
+        assert_eq!(
+            edit_script("kitten", "sitting"),
+            [
+                Edit::Substitute { lhs: 0, rhs: 0 },
+                Edit::Match { lhs: 1, rhs: 1 },
+                Edit::Match { lhs: 2, rhs: 2 },
+                Edit::Match { lhs: 3, rhs: 3 },
+                Edit::Substitute { lhs: 4, rhs: 4 },
+                Edit::Match { lhs: 5, rhs: 5 },
+                Edit::Insert { rhs: 6 },
+            ]
+        );
+        assert_eq!(
+            edit_script("", "ab"),
+            [Edit::Insert { rhs: 0 }, Edit::Insert { rhs: 1 }]
+        );
+        assert_eq!(
+            edit_script("ab", ""),
+            [Edit::Delete { lhs: 0 }, Edit::Delete { lhs: 1 }]
+        );
+        assert_eq!(edit_script("", ""), []);
+
+        let mut rng = rand::rng();
+        for _ in 0..500 {
+            let lhs: String = (0..rng.random_range(0..30))
+                .map(|_| rng.random_range('a'..='d'))
+                .collect();
+            let rhs: String = (0..rng.random_range(0..30))
+                .map(|_| rng.random_range('a'..='d'))
+                .collect();
+            let script = edit_script(&lhs, &rhs);
+            assert_eq!(check_script(&lhs, &rhs, &script), calculate(&lhs, &rhs));
+        }
+    }
+
+    #[test]
+    fn test_align() {
+        let alignment = align("kitten", "sitting");
+        assert_eq!(alignment.lhs, "kitten-");
+        assert_eq!(alignment.rhs, "sitting");
+        assert_eq!(alignment.to_string(), "kitten-\nsitting");
+
+        let alignment = align("intention", "execution");
+        assert_eq!(alignment.lhs.chars().count(), alignment.rhs.chars().count());
+        assert_eq!(alignment.lhs.replace(GAP, ""), "intention");
+        assert_eq!(alignment.rhs.replace(GAP, ""), "execution");
+    }
+
+    // (lhs, rhs, distance in chars, in graphemes, in bytes)
+    const UNICODE_CORPUS: [(&str, &str, usize, usize, usize); 15] = [
+        ("\u{e9}", "", 1, 1, 2),
+        ("Jos\u{e9}", "Jose", 1, 1, 2),
+        // Precomposed é against e with a combining acute accent
+        ("Jos\u{e9}", "Jose\u{301}", 2, 1, 3),
+        ("M\u{fc}ller", "Mueller", 2, 2, 2),
+        ("Zo\u{eb}", "Zoe", 1, 1, 2),
+        ("S\u{e3}o Paulo", "Sao Paulo", 1, 1, 2),
+        ("\u{c5}ngstr\u{f6}m", "Angstrom", 2, 2, 4),
+        ("\u{d1}and\u{fa}", "Nandu", 2, 2, 4),
+        ("\u{df}", "ss", 2, 2, 2),
+        (
+            "\u{410}\u{43b}\u{435}\u{43a}\u{441}\u{430}\u{43d}\u{434}\u{440}",
+            "\u{410}\u{43b}\u{435}\u{43a}\u{441}\u{435}\u{439}",
+            4,
+            4,
+            6,
+        ),
+        ("\u{6771}\u{4eac}", "\u{4eac}\u{90fd}", 2, 2, 6),
+        (
+            "\u{d55c}\u{ad6d}\u{c5b4}",
+            "\u{d55c}\u{ad6d}\u{b9d0}",
+            1,
+            1,
+            3,
+        ),
+        (
+            "\u{928}\u{92e}\u{938}\u{94d}\u{924}\u{947}",
+            "\u{928}\u{92e}\u{938}\u{94d}\u{915}\u{93e}\u{930}",
+            3,
+            2,
+            5,
+        ),
+        // Families joined by zero width joiners, differing in the last member
+        (
+            "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}",
+            "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f466}",
+            1,
+            1,
+            1,
+        ),
+        // Flags are pairs of regional indicators
+        ("\u{1f1eb}\u{1f1f7}", "\u{1f1e9}\u{1f1ea}", 2, 1, 2),
+    ];
+
+    #[test]
+    fn test_unicode_corpus() {
+        for (lhs, rhs, chars, graphemes, bytes) in UNICODE_CORPUS {
+            assert_eq!(calculate_chars(lhs, rhs), chars, "{lhs} {rhs}");
+            assert_eq!(calculate_chars(rhs, lhs), chars, "{rhs} {lhs}");
+            assert_eq!(calculate(lhs, rhs), chars, "{lhs} {rhs}");
+            assert_eq!(calculate_graphemes(lhs, rhs), graphemes, "{lhs} {rhs}");
+            assert_eq!(calculate_graphemes(rhs, lhs), graphemes, "{rhs} {lhs}");
+            assert_eq!(calculate_bytes(lhs, rhs), bytes, "{lhs} {rhs}");
+            assert_eq!(calculate_bytes(rhs, lhs), bytes, "{rhs} {lhs}");
+
+            for unit in [calculate_chars, calculate_graphemes, calculate_bytes] {
+                assert_eq!(unit(lhs, lhs), 0);
+            }
+            assert_eq!(calculate_chars(lhs, ""), lhs.chars().count());
+            assert_eq!(calculate_graphemes("", rhs), rhs.graphemes(true).count());
+            assert_eq!(calculate_bytes(lhs, ""), lhs.len());
+        }
+    }
+
+    #[test]
+    fn test_distance_with_costs() {
+        let lhs: Vec<char> = "kitten".chars().collect();
+        let rhs: Vec<char> = "sitting".chars().collect();
+        assert_eq!(distance(&lhs, &rhs, &UnitCosts), 3);
+        // Substitutions cost as much as a deletion and an insertion: 13 - 2 * |"ittn"|
+        let indel = WeightedCosts {
+            insert: 1,
+            delete: 1,
+            substitute: 2,
+        };
+        assert_eq!(distance(&lhs, &rhs, &indel), 5);
+
+        let tokens = ["the", "quick", "brown", "fox"];
+        assert_eq!(
+            distance(&tokens, &["the", "slow", "brown", "fox"], &UnitCosts),
+            1
+        );
+        assert_eq!(distance(&tokens, &["quick", "fox"], &UnitCosts), 2);
+
+        // Asymmetric costs stay attached to the operation, whichever side is longer
+        let cheap_inserts = WeightedCosts {
+            insert: 1,
+            delete: 3,
+            substitute: 10,
+        };
+        assert_eq!(distance(b"ab", b"", &cheap_inserts), 6);
+        assert_eq!(distance(b"", b"ab", &cheap_inserts), 2);
+        assert_eq!(distance(b"a", b"abc", &cheap_inserts), 2);
+        assert_eq!(distance(b"abc", b"a", &cheap_inserts), 6);
+        assert_eq!(distance(b"ab", b"ba", &cheap_inserts), 4);
+    }
+
+    // Substituting a key by one next to it on a QWERTY keyboard is a likely typo
+    struct KeyboardCosts;
+
+    const ROWS: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+    fn key_position(key: char) -> Option<(i32, i32)> {
+        ROWS.iter()
+            .enumerate()
+            .find_map(|(row, keys)| keys.find(key).map(|column| (row as i32, column as i32)))
+    }
+
+    impl EditCosts<char> for KeyboardCosts {
+        fn insert(&self, _element: &char) -> usize {
+            2
+        }
+
+        fn delete(&self, _element: &char) -> usize {
+            2
+        }
+
+        fn substitute(&self, from: &char, to: &char) -> usize {
+            match (key_position(*from), key_position(*to)) {
+                (Some((lhs_row, lhs_column)), Some((rhs_row, rhs_column)))
+                    if (lhs_row - rhs_row).abs() <= 1 && (lhs_column - rhs_column).abs() <= 1 =>
+                {
+                    1
+                }
+                _ => 3,
+            }
+        }
+    }
+
+    #[test]
+    fn test_keyboard_costs() {
+        let keyboard = |lhs: &str, rhs: &str| {
+            let lhs: Vec<char> = lhs.chars().collect();
+            let rhs: Vec<char> = rhs.chars().collect();
+            distance(&lhs, &rhs, &KeyboardCosts)
+        };
+        assert_eq!(keyboard("cat", "cat"), 0);
+        assert_eq!(keyboard("cat", "vat"), 1);
+        assert_eq!(keyboard("cat", "hat"), 3);
+        assert_eq!(keyboard("cat", "at"), 2);
+        assert_eq!(keyboard("hello", "jello"), 1);
+    }
+
+    #[test]
+    fn test_calculate_bounded() {
+        assert_eq!(calculate_bounded("kitten", "sitting", 3), Some(3));
+        assert_eq!(calculate_bounded("kitten", "sitting", 2), None);
+        assert_eq!(calculate_bounded("kitten", "sitting", usize::MAX), Some(3));
+        assert_eq!(calculate_bounded("", "", 0), Some(0));
+        assert_eq!(calculate_bounded("abc", "", 3), Some(3));
+        assert_eq!(calculate_bounded("", "abc", 2), None);
+        assert_eq!(calculate_bounded("\u{e9}", "e", 1), Some(1));
+
+        let mut rng = rand::rng();
+        for _ in 0..2000 {
+            let lhs: String = (0..rng.random_range(0..15))
+                .map(|_| rng.random_range('a'..='c'))
+                .collect();
+            let rhs: String = (0..rng.random_range(0..15))
+                .map(|_| rng.random_range('a'..='c'))
+                .collect();
+            let distance = calculate(&lhs, &rhs);
+            for max in 0..=16 {
+                assert_eq!(
+                    calculate_bounded(&lhs, &rhs, max),
+                    (distance <= max).then_some(distance),
+                    "{lhs} {rhs} {max}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_calculate_bounded_on_long_strings() {
+        // Far too long for the full table, but only a thin band is computed
+        let mut rng = rand::rng();
+        let lhs: String = (0..200_000).map(|_| rng.random_range('a'..='z')).collect();
+        let mut rhs = lhs.clone();
+        rhs.insert(1000, 'x');
+        rhs.replace_range(150_000..150_001, "\u{e9}");
+        assert_eq!(calculate_bounded(&lhs, &rhs, 2), Some(2));
+        assert_eq!(calculate_bounded(&lhs, &rhs, 1), None);
+
+        // Unrelated strings stop after the first few rows
+        let other: String = (0..200_000).map(|_| rng.random_range('0'..='9')).collect();
+        assert_eq!(calculate_bounded(&lhs, &other, 5), None);
+    }
+
+    // `distance` with unit costs runs the bit-parallel algorithm itself
+    fn dp_distance<T: Eq>(lhs: &[T], rhs: &[T]) -> usize {
+        last_row(lhs.iter(), rhs.iter(), &UnitCosts)[rhs.len()]
+    }
+
+    #[test]
+    fn test_bit_parallel_against_dp() {
+        let mut rng = rand::rng();
+        let mut lengths: Vec<usize> = vec![0, 1, 2, 63, 64, 65, 127, 128, 129, 200];
+        lengths.extend((0..30).map(|_| rng.random_range(0..300)));
+
+        for &lhs_len in &lengths {
+            for &rhs_len in &lengths {
+                for alphabet in [2, 4, 26] {
+                    let letters = (b'a'..b'a' + alphabet).map(char::from);
+                    let letters: Vec<char> = letters.collect();
+                    let lhs: Vec<char> = (0..lhs_len)
+                        .map(|_| letters[rng.random_range(0..letters.len())])
+                        .collect();
+                    let rhs: Vec<char> = (0..rhs_len)
+                        .map(|_| letters[rng.random_range(0..letters.len())])
+                        .collect();
+
+                    let expected = dp_distance(&lhs, &rhs);
+                    assert_eq!(distance(&lhs, &rhs, &UnitCosts), expected);
+                    assert_eq!(
+                        bit_parallel_distance(&lhs, &rhs),
+                        expected,
+                        "{lhs_len} {rhs_len}"
+                    );
+                    assert_eq!(
+                        bit_parallel_distance(&rhs, &lhs),
+                        expected,
+                        "{rhs_len} {lhs_len}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mask_tables_and_kernels_agree() {
+        let mut rng = rand::rng();
+        for _ in 0..300 {
+            let top = if rng.random() { b'c' } else { u8::MAX };
+            let len = rng.random_range(1..=150);
+            let pattern: Vec<u8> = (0..len).map(|_| rng.random_range(b'a'..=top)).collect();
+            let text: Vec<u8> = (0..rng.random_range(0..200))
+                .map(|_| rng.random_range(b'a'..=top))
+                .collect();
+
+            let expected = dp_distance(&pattern, &text);
+            let bytes = ByteMasks::new(&pattern);
+            let sorted = SortedMasks::new(&pattern);
+            assert_eq!(block_distance(len, &bytes, &text), expected);
+            assert_eq!(block_distance(len, &sorted, &text), expected);
+            if len <= WORD_BITS {
+                assert_eq!(single_word_distance(len, &bytes, &text), expected);
+                assert_eq!(single_word_distance(len, &sorted, &text), expected);
+            }
+            assert_eq!(unit_distance_bytes(&pattern, &text), expected);
+            assert_eq!(unit_distance(&text, &pattern), expected);
+        }
+    }
 }
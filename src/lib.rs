@@ -1,8 +1,12 @@
 pub mod binary_search;
+pub mod bk_tree;
+pub mod fuzzy_trie;
+pub mod generators;
 pub mod left_nearest_number;
 pub mod levenshtein_distance;
 pub mod longest_increasing_subsequence;
 pub mod longest_subsequence_below_or_equal_m;
 pub mod sort;
 pub mod stack_with_constant_max;
+pub mod string_distance;
 pub mod two_stack_queue;
@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::collections::VecDeque;
 
 pub type Input<'a, T> = &'a [T];
@@ -7,6 +8,20 @@ pub type Output = Vec<usize>;
 // Time: O(n^2)
 // Space: O(n)
 pub fn find<T: Ord>(input: Input<T>) -> Option<Output> {
+    find_by(input, &mut T::cmp)
+}
+
+pub fn find_by_key<T, K: Ord, F>(input: Input<T>, f: &mut F) -> Option<Output>
+where
+    F: FnMut(&T) -> K,
+{
+    find_by(input, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)))
+}
+
+pub fn find_by<T, F>(input: Input<T>, compare: &mut F) -> Option<Output>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
     if input.is_empty() {
         return None;
     }
@@ -18,7 +33,7 @@ pub fn find<T: Ord>(input: Input<T>) -> Option<Output> {
     for i in 1..n {
         let mut index_of_max = None;
         for j in 0..i {
-            if input[i] > input[j]
+            if compare(&input[i], &input[j]) == Ordering::Greater
                 && index_of_max
                     .map(|m| lengths_of_subsequence[j] > lengths_of_subsequence[m])
                     .unwrap_or(true)
@@ -59,6 +74,23 @@ pub fn find<T: Ord>(input: Input<T>) -> Option<Output> {
 // Space: O(n)
 // Lexicographically smallest in term of indices
 pub fn find_lexicographically_smallest<T: Ord>(input: Input<T>) -> Option<Output> {
+    find_lexicographically_smallest_by(input, &mut T::cmp)
+}
+
+pub fn find_lexicographically_smallest_by_key<T, K: Ord, F>(
+    input: Input<T>,
+    f: &mut F,
+) -> Option<Output>
+where
+    F: FnMut(&T) -> K,
+{
+    find_lexicographically_smallest_by(input, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)))
+}
+
+pub fn find_lexicographically_smallest_by<T, F>(input: Input<T>, compare: &mut F) -> Option<Output>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
     if input.is_empty() {
         return None;
     }
@@ -70,7 +102,7 @@ pub fn find_lexicographically_smallest<T: Ord>(input: Input<T>) -> Option<Output
     for i in (0..n).rev() {
         let mut index_of_max = None;
         for j in (i + 1)..n {
-            if input[i] < input[j]
+            if compare(&input[i], &input[j]) == Ordering::Less
                 && index_of_max
                     .map(|m| lengths_of_subsequence[j] > lengths_of_subsequence[m])
                     .unwrap_or(true)
@@ -84,9 +116,12 @@ pub fn find_lexicographically_smallest<T: Ord>(input: Input<T>) -> Option<Output
         }
     }
 
+    // The longest subsequence starting first: max_by_key returns the last
+    // maximum, so it goes through the indices backwards
     let (index, max) = lengths_of_subsequence
         .iter()
         .enumerate()
+        .rev()
         .max_by_key(|&(_, value)| value)?;
 
     if *max == 1 {
@@ -132,4 +167,49 @@ mod tests {
         let expected = vec![0, 2, 4];
         assert_eq!(find(&input), Some(expected));
     }
+
+    // Several longest subsequences start at different indices: the first start
+    // has to win, not the last
+    #[test]
+    fn test_lexicographically_smallest_picks_the_first_start() {
+        let cases: [(&[i32], [usize; 2]); 4] = [
+            (&[0, 0, 1], [0, 2]),
+            (&[2, 2, 3, 3], [0, 2]),
+            (&[5, 1, 1, 2], [1, 3]),
+            (&[1, 2, 0, 1], [0, 1]),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                find_lexicographically_smallest(input),
+                Some(expected.to_vec()),
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_by_and_by_key() {
+        // Longest decreasing subsequence through a reversed comparator
+        let input = vec![5, 1, 4, 2, 3, 0];
+        let expected = vec![0, 2, 3, 5];
+        assert_eq!(
+            find_by(&input, &mut |lhs, rhs| rhs.cmp(lhs)),
+            Some(expected)
+        );
+
+        let input = vec![-1.0, 2.25, 0.5, 1.0, 3.5];
+        let expected = vec![0, 2, 3, 4];
+        assert_eq!(find_by(&input, &mut f64::total_cmp), Some(expected));
+
+        let input = vec![("a", 4), ("b", 1), ("c", 2), ("d", 1), ("e", 3)];
+        let expected = vec![1, 2, 4];
+        assert_eq!(
+            find_by_key(&input, &mut |record| record.1),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            find_lexicographically_smallest_by_key(&input, &mut |record| record.1),
+            Some(expected)
+        );
+    }
 }
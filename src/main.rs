@@ -1,3 +1,599 @@
-fn main() {
-    println!("Hello, world!");
+use cs::{
+    binary_search, left_nearest_number, levenshtein_distance, longest_increasing_subsequence,
+    longest_subsequence_below_or_equal_m, sort,
+};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::num::{IntErrorKind, ParseIntError};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+const USAGE: &str = "\
+usage: cs <command> [options] [file...]
+
+Numbers are read whitespace-separated from the files, or from stdin when no
+file (or -) is given.
+
+commands:
+  sort [--algo name] [--lines]     sort numbers, or lines with --lines
+                                   algorithms: quick (default), lomuto, intro, merge,
+                                   stable-merge, tim, heap, shell, insertion,
+                                   binary-insertion, selection, radix, par-quick, par-merge
+  search <bound> <target>          search sorted numbers, bound is one of
+                                   lower-bound, upper-bound, equal-range
+  lis                              longest increasing subsequence, one value per line
+  edit-distance <lhs> <rhs> [--align]
+                                   Levenshtein distance between two strings, followed
+                                   by the two strings aligned with --align
+  nearest-smaller                  for every number the index of the nearest smaller
+                                   number to its left, or - if there is none
+  max-window-sum --limit <m>       longest window of non-negative numbers whose sum is
+                                   at most m, printed as a half-open range start..end,
+                                   or - if no number is at most m
+  help                             print this message";
+
+#[derive(Debug)]
+enum Error {
+    Usage(String),
+    Io {
+        source: String,
+        error: io::Error,
+    },
+    Parse {
+        source: String,
+        line: usize,
+        token: String,
+        error: ParseIntError,
+    },
+    Input(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Usage(message) => write!(f, "{message}\n\n{USAGE}"),
+            Error::Io { source, error } => write!(f, "{source}: {error}"),
+            Error::Parse {
+                source,
+                line,
+                token,
+                error,
+            } => write!(f, "{source}:{line}: '{token}' {}", parse_failure(error)),
+            Error::Input(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl Error {
+    // Mistakes in the arguments exit with 2, problems with the data with 1
+    fn exit_code(&self) -> u8 {
+        match self {
+            Error::Usage(_) => 2,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Algorithm {
+    Quick,
+    Lomuto,
+    Intro,
+    Merge,
+    StableMerge,
+    Tim,
+    Heap,
+    Shell,
+    Insertion,
+    BinaryInsertion,
+    Selection,
+    Radix,
+    ParQuick,
+    ParMerge,
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Error> {
+        Ok(match name {
+            "quick" => Algorithm::Quick,
+            "lomuto" => Algorithm::Lomuto,
+            "intro" => Algorithm::Intro,
+            "merge" => Algorithm::Merge,
+            "stable-merge" => Algorithm::StableMerge,
+            "tim" => Algorithm::Tim,
+            "heap" => Algorithm::Heap,
+            "shell" => Algorithm::Shell,
+            "insertion" => Algorithm::Insertion,
+            "binary-insertion" => Algorithm::BinaryInsertion,
+            "selection" => Algorithm::Selection,
+            "radix" => Algorithm::Radix,
+            "par-quick" => Algorithm::ParQuick,
+            "par-merge" => Algorithm::ParMerge,
+            _ => return Err(Error::Usage(format!("unknown sort algorithm '{name}'"))),
+        })
+    }
+}
+
+impl Algorithm {
+    // The radix sorts depend on the element type, so the caller provides one
+    fn sort<T: Ord + Send>(self, input: &mut [T], radix_sort: fn(&mut [T])) {
+        match self {
+            Algorithm::Quick => sort::quick_sort(input, &mut rand::rng()),
+            Algorithm::Lomuto => sort::lomuto_quick_sort(input, &mut rand::rng()),
+            Algorithm::Intro => sort::intro_sort(input),
+            Algorithm::Merge => sort::merge_sort(input),
+            Algorithm::StableMerge => sort::stable_merge_sort(input),
+            Algorithm::Tim => sort::tim_sort(input),
+            Algorithm::Heap => sort::heap_sort(input),
+            Algorithm::Shell => sort::shell_sort(input, sort::ShellGaps::default()),
+            Algorithm::Insertion => sort::insertion_sort(input),
+            Algorithm::BinaryInsertion => sort::binary_insertion_sort(input),
+            Algorithm::Selection => sort::selection_sort(input),
+            Algorithm::Radix => radix_sort(input),
+            Algorithm::ParQuick => sort::par_quick_sort(input, &sort::ParallelOptions::default()),
+            Algorithm::ParMerge => sort::par_merge_sort(input, &sort::ParallelOptions::default()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bound {
+    Lower,
+    Upper,
+    EqualRange,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Sort {
+        algorithm: Algorithm,
+        lines: bool,
+    },
+    Search {
+        bound: Bound,
+        target: i64,
+    },
+    Lis,
+    EditDistance {
+        lhs: String,
+        rhs: String,
+        align: bool,
+    },
+    NearestSmaller,
+    MaxWindowSum {
+        limit: i64,
+    },
+    Help,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Invocation {
+    command: Command,
+    files: Vec<String>,
+}
+
+// What is wrong with a number that failed to parse
+fn parse_failure(error: &ParseIntError) -> &'static str {
+    match error.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+            "is out of the range of 64-bit integers"
+        }
+        _ => "is not an integer",
+    }
+}
+
+fn parse_number(what: &str, value: Option<String>) -> Result<i64, Error> {
+    let value = value.ok_or_else(|| Error::Usage(format!("missing {what}")))?;
+    value
+        .parse()
+        .map_err(|error| Error::Usage(format!("{what} '{value}' {}", parse_failure(&error))))
+}
+
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Invocation, Error> {
+    let mut args = args.into_iter();
+    let name = args
+        .next()
+        .ok_or_else(|| Error::Usage("missing command".to_string()))?;
+
+    let mut command = match name.as_str() {
+        "sort" => Command::Sort {
+            algorithm: Algorithm::Quick,
+            lines: false,
+        },
+        "search" => {
+            let bound = match args.next().as_deref() {
+                Some("lower-bound") => Bound::Lower,
+                Some("upper-bound") => Bound::Upper,
+                Some("equal-range") => Bound::EqualRange,
+                Some(other) => return Err(Error::Usage(format!("unknown bound '{other}'"))),
+                None => return Err(Error::Usage("missing bound".to_string())),
+            };
+            let target = parse_number("target", args.next())?;
+            Command::Search { bound, target }
+        }
+        "lis" => Command::Lis,
+        "edit-distance" => {
+            let (Some(lhs), Some(rhs)) = (args.next(), args.next()) else {
+                return Err(Error::Usage("edit-distance needs two strings".to_string()));
+            };
+            Command::EditDistance {
+                lhs,
+                rhs,
+                align: false,
+            }
+        }
+        "nearest-smaller" => Command::NearestSmaller,
+        "max-window-sum" => Command::MaxWindowSum { limit: i64::MAX },
+        "help" | "--help" | "-h" => Command::Help,
+        _ => return Err(Error::Usage(format!("unknown command '{name}'"))),
+    };
+
+    let mut files = Vec::new();
+    let mut limit_given = false;
+    while let Some(arg) = args.next() {
+        match (&mut command, arg.as_str()) {
+            (Command::Sort { algorithm, .. }, "--algo") => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| Error::Usage("missing algorithm".to_string()))?;
+                *algorithm = name.parse()?;
+            }
+            (Command::Sort { lines, .. }, "--lines") => *lines = true,
+            (Command::MaxWindowSum { limit }, "--limit") => {
+                *limit = parse_number("limit", args.next())?;
+                limit_given = true;
+            }
+            (Command::EditDistance { align, .. }, "--align") => *align = true,
+            (Command::EditDistance { .. } | Command::Help, _) => {
+                return Err(Error::Usage(format!("unexpected argument '{arg}'")));
+            }
+            (_, "-") => files.push(arg),
+            (_, option) if option.starts_with('-') => {
+                return Err(Error::Usage(format!("unknown option '{option}'")));
+            }
+            _ => files.push(arg),
+        }
+    }
+    if matches!(command, Command::MaxWindowSum { .. }) && !limit_given {
+        return Err(Error::Usage("max-window-sum needs --limit".to_string()));
+    }
+
+    Ok(Invocation { command, files })
+}
+
+// The contents of every input with the name used in error messages
+fn read_inputs<R: Read>(files: &[String], mut stdin: R) -> Result<Vec<(String, String)>, Error> {
+    if files.is_empty() {
+        return read_inputs(&["-".to_string()], stdin);
+    }
+
+    let mut inputs = Vec::new();
+    for file in files {
+        let mut contents = String::new();
+        let (source, result) = if file == "-" {
+            ("<stdin>".to_string(), stdin.read_to_string(&mut contents))
+        } else {
+            let result = File::open(file).and_then(|mut f| f.read_to_string(&mut contents));
+            (file.clone(), result)
+        };
+        result.map_err(|error| Error::Io {
+            source: source.clone(),
+            error,
+        })?;
+        inputs.push((source, contents));
+    }
+    Ok(inputs)
+}
+
+fn parse_numbers(inputs: &[(String, String)]) -> Result<Vec<i64>, Error> {
+    let mut numbers = Vec::new();
+    for (source, contents) in inputs {
+        for (index, line) in contents.lines().enumerate() {
+            for token in line.split_whitespace() {
+                let number = token.parse().map_err(|error| Error::Parse {
+                    source: source.clone(),
+                    line: index + 1,
+                    token: token.to_string(),
+                    error,
+                })?;
+                numbers.push(number);
+            }
+        }
+    }
+    Ok(numbers)
+}
+
+fn run<R: Read, W: Write>(invocation: &Invocation, stdin: R, stdout: W) -> Result<(), Error> {
+    let mut out = BufWriter::new(stdout);
+    write_output(invocation, stdin, &mut out).and_then(|()| out.flush().map_err(stdout_error))
+}
+
+fn stdout_error(error: io::Error) -> Error {
+    Error::Io {
+        source: "<stdout>".to_string(),
+        error,
+    }
+}
+
+fn write_output<R: Read, W: Write>(
+    invocation: &Invocation,
+    stdin: R,
+    out: &mut W,
+) -> Result<(), Error> {
+    let inputs = match invocation.command {
+        Command::Help | Command::EditDistance { .. } => Vec::new(),
+        _ => read_inputs(&invocation.files, stdin)?,
+    };
+    let numbers = || parse_numbers(&inputs);
+
+    match &invocation.command {
+        Command::Help => writeln!(out, "{USAGE}"),
+        Command::Sort {
+            algorithm,
+            lines: true,
+        } => {
+            let mut lines: Vec<&str> = inputs
+                .iter()
+                .flat_map(|(_, contents)| contents.lines())
+                .collect();
+            algorithm.sort(&mut lines, sort::msd_radix_sort);
+            lines.iter().try_for_each(|line| writeln!(out, "{line}"))
+        }
+        Command::Sort {
+            algorithm,
+            lines: false,
+        } => {
+            let mut numbers = numbers()?;
+            algorithm.sort(&mut numbers, sort::lsd_radix_sort);
+            numbers
+                .iter()
+                .try_for_each(|number| writeln!(out, "{number}"))
+        }
+        Command::Search { bound, target } => {
+            let numbers = numbers()?;
+            if !numbers.is_sorted() {
+                return Err(Error::Input("search needs sorted input".to_string()));
+            }
+            match bound {
+                Bound::Lower => writeln!(out, "{}", binary_search::lower_bound(&numbers, target)),
+                Bound::Upper => writeln!(out, "{}", binary_search::upper_bound(&numbers, target)),
+                Bound::EqualRange => {
+                    let range = binary_search::equal_range(&numbers, target);
+                    writeln!(out, "{}..{}", range.start, range.end)
+                }
+            }
+        }
+        Command::Lis => {
+            let numbers = numbers()?;
+            let indices = match longest_increasing_subsequence::find(&numbers) {
+                Some(indices) => indices,
+                // A single element is the longest increasing subsequence
+                None => (0..numbers.len().min(1)).collect(),
+            };
+            indices
+                .iter()
+                .try_for_each(|&index| writeln!(out, "{}", numbers[index]))
+        }
+        Command::EditDistance { lhs, rhs, align } => {
+            writeln!(out, "{}", levenshtein_distance::calculate(lhs, rhs)).and_then(|()| {
+                if *align {
+                    writeln!(out, "{}", levenshtein_distance::align(lhs, rhs))
+                } else {
+                    Ok(())
+                }
+            })
+        }
+        Command::NearestSmaller => {
+            let numbers = numbers()?;
+            left_nearest_number::find(&numbers)
+                .iter()
+                .try_for_each(|index| match index {
+                    Some(index) => writeln!(out, "{index}"),
+                    None => writeln!(out, "-"),
+                })
+        }
+        Command::MaxWindowSum { limit } => {
+            let numbers = numbers()?;
+            if numbers.iter().any(|&number| number < 0) {
+                return Err(Error::Input(
+                    "max-window-sum needs non-negative numbers".to_string(),
+                ));
+            }
+            // Every window sum is at most the total, whatever the limit
+            if numbers
+                .iter()
+                .try_fold(0i64, |sum, &number| sum.checked_add(number))
+                .is_none()
+            {
+                return Err(Error::Input("the numbers are too large to sum".to_string()));
+            }
+            match longest_subsequence_below_or_equal_m::find(&numbers, *limit) {
+                Some(window) => writeln!(out, "{}..{}", window.left, window.right),
+                None => writeln!(out, "-"),
+            }
+        }
+    }
+    .map_err(stdout_error)
+}
+
+fn main() -> ExitCode {
+    let result = parse_args(std::env::args().skip(1))
+        .and_then(|invocation| run(&invocation, io::stdin().lock(), io::stdout().lock()));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("cs: {error}");
+            ExitCode::from(error.exit_code())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn run_line(line: &str, stdin: &str) -> Result<String, Error> {
+        let invocation = parse_args(args(line))?;
+        let mut stdout = Vec::new();
+        run(&invocation, stdin.as_bytes(), &mut stdout)?;
+        Ok(String::from_utf8(stdout).unwrap())
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(args("sort --algo merge a.txt -")).unwrap(),
+            Invocation {
+                command: Command::Sort {
+                    algorithm: Algorithm::Merge,
+                    lines: false
+                },
+                files: vec!["a.txt".to_string(), "-".to_string()],
+            }
+        );
+        assert_eq!(
+            parse_args(args("search equal-range -3")).unwrap().command,
+            Command::Search {
+                bound: Bound::EqualRange,
+                target: -3
+            }
+        );
+        assert_eq!(
+            parse_args(args("max-window-sum --limit 10"))
+                .unwrap()
+                .command,
+            Command::MaxWindowSum { limit: 10 }
+        );
+
+        for line in [
+            "",
+            "shuffle",
+            "sort --algo bogo",
+            "sort --algo",
+            "sort --reverse",
+            "search middle 3",
+            "search lower-bound x",
+            "edit-distance kitten",
+            "edit-distance a b c",
+            "max-window-sum",
+            "lis --limit 3",
+        ] {
+            let error = parse_args(args(line)).unwrap_err();
+            assert!(matches!(error, Error::Usage(_)), "{line}");
+            assert_eq!(error.exit_code(), 2);
+        }
+    }
+
+    #[test]
+    fn test_sort() {
+        for algorithm in [
+            "quick",
+            "lomuto",
+            "intro",
+            "merge",
+            "stable-merge",
+            "tim",
+            "heap",
+            "shell",
+            "insertion",
+            "binary-insertion",
+            "selection",
+            "radix",
+            "par-quick",
+            "par-merge",
+        ] {
+            let output = run_line(&format!("sort --algo {algorithm}"), "3 -1\n2\n\n10 0").unwrap();
+            assert_eq!(output, "-1\n0\n2\n3\n10\n", "{algorithm}");
+        }
+        assert_eq!(run_line("sort", "").unwrap(), "");
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(
+            run_line("search lower-bound 3", "1 2 3 3 5").unwrap(),
+            "2\n"
+        );
+        assert_eq!(
+            run_line("search upper-bound 3", "1 2 3 3 5").unwrap(),
+            "4\n"
+        );
+        assert_eq!(
+            run_line("search equal-range 4", "1 2 3 3 5").unwrap(),
+            "4..4\n"
+        );
+        assert_eq!(
+            run_line("lis", "10 22 9 33 21 50 41 60 80").unwrap(),
+            "10\n22\n33\n50\n60\n80\n"
+        );
+        assert_eq!(run_line("lis", "3 2 1").unwrap(), "3\n");
+        assert_eq!(run_line("lis", "").unwrap(), "");
+        assert_eq!(run_line("edit-distance kitten sitting", "").unwrap(), "3\n");
+        assert_eq!(
+            run_line("edit-distance kitten sitting --align", "").unwrap(),
+            "3\nkitten-\nsitting\n"
+        );
+        assert_eq!(
+            run_line("nearest-smaller", "4 5 2 10 8").unwrap(),
+            "-\n0\n-\n2\n2\n"
+        );
+        assert_eq!(
+            run_line("max-window-sum --limit 5", "1 2 3").unwrap(),
+            "0..2\n"
+        );
+        assert_eq!(run_line("max-window-sum --limit 5", "6 7").unwrap(), "-\n");
+        assert_eq!(
+            run_line(&format!("max-window-sum --limit {}", i64::MAX), "1 2 3").unwrap(),
+            "0..3\n"
+        );
+        assert!(run_line("help", "").unwrap().starts_with("usage"));
+    }
+
+    #[test]
+    fn test_input_errors() {
+        let error = run_line("sort", "1 2\n3 x4").unwrap_err();
+        assert_eq!(error.to_string(), "<stdin>:2: 'x4' is not an integer");
+        assert_eq!(error.exit_code(), 1);
+        let error = run_line("sort", "1 99999999999999999999").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "<stdin>:1: '99999999999999999999' is out of the range of 64-bit integers"
+        );
+        let error = run_line("sort", "-99999999999999999999").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .ends_with("is out of the range of 64-bit integers")
+        );
+        let error = run_line("max-window-sum --limit 99999999999999999999", "").unwrap_err();
+        assert!(matches!(error, Error::Usage(_)));
+        assert!(
+            error
+                .to_string()
+                .starts_with("limit '99999999999999999999' is out of the range of 64-bit integers")
+        );
+
+        let error = run_line("search lower-bound 1", "3 2 1").unwrap_err();
+        assert_eq!(error.to_string(), "search needs sorted input");
+
+        let error = run_line("max-window-sum --limit 5", "1 -2").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "max-window-sum needs non-negative numbers"
+        );
+        let error = run_line("max-window-sum --limit 5", &format!("{} 1", i64::MAX)).unwrap_err();
+        assert_eq!(error.to_string(), "the numbers are too large to sum");
+
+        let error = run_line("lis missing-file.txt", "").unwrap_err();
+        assert!(matches!(error, Error::Io { .. }));
+        assert!(error.to_string().starts_with("missing-file.txt: "));
+        assert_eq!(error.exit_code(), 1);
+    }
 }
@@ -1,70 +1,393 @@
-use rand::seq::IndexedRandom;
+use crate::binary_search::partition_point;
+use observer::{compare_at, swap_at};
+use std::cmp::Ordering;
+use std::ops::Range;
+
+mod external;
+mod k_way_merge;
+mod observer;
+mod parallel;
+mod radix;
+mod select;
+mod tim_sort;
+
+pub use external::{
+    ChunkSort, ExternalSortOptions, ExternalSortSummary, FixedWidthCodec, LinesCodec, RecordCodec,
+    external_sort, external_sort_by, external_sort_by_key,
+};
+pub use k_way_merge::{KWayMerge, Merge, merge, merge_by};
+pub use observer::{Counters, Operation, SortObserver, Trace};
+pub use parallel::{
+    ParallelOptions, par_merge_sort, par_merge_sort_by, par_merge_sort_by_key, par_quick_sort,
+    par_quick_sort_by, par_quick_sort_by_key,
+};
+pub use radix::{
+    RadixKey, bucket_sort, counting_sort, counting_sort_by_key, lsd_radix_sort,
+    lsd_radix_sort_by_key, msd_radix_sort,
+};
+pub use select::{
+    nth_element, nth_element_by, nth_element_by_key, partial_sort, partial_sort_by,
+    partial_sort_by_key, quickselect, quickselect_by, top_k, top_k_by, top_k_by_key,
+};
+pub use tim_sort::{tim_sort, tim_sort_by, tim_sort_by_key};
 
 pub type InPlaceInput<'a, T> = &'a mut [T];
 
 pub fn selection_sort<T: Ord>(input: InPlaceInput<T>) {
+    selection_sort_by(input, &mut T::cmp);
+}
+
+pub fn selection_sort_by_key<T, K: Ord, F>(input: InPlaceInput<T>, f: &mut F)
+where
+    F: FnMut(&T) -> K,
+{
+    selection_sort_by(input, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn selection_sort_by<T, F>(input: InPlaceInput<T>, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    selection_sort_by_observed(input, compare, &mut ());
+}
+
+// Selection sort reporting its operations to `observer`.
+// It always makes exactly n(n-1)/2 comparisons and at most n-1 swaps.
+pub fn selection_sort_observed<T: Ord, O: SortObserver>(input: InPlaceInput<T>, observer: &mut O) {
+    selection_sort_by_observed(input, &mut T::cmp, observer);
+}
+
+pub fn selection_sort_by_observed<T, F, O>(
+    input: InPlaceInput<T>,
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
     for i in 0..input.len() {
-        let min_index = input[i..]
-            .iter()
-            .enumerate()
-            .min_by(|a, b| a.1.cmp(b.1))
-            .map(|(index, _)| index + i)
-            .unwrap_or(i);
+        // The first of equal minimums is kept
+        let mut min_index = i;
+        for j in i + 1..input.len() {
+            if compare_at(input, 0, j, min_index, compare, observer) == Ordering::Less {
+                min_index = j;
+            }
+        }
         if min_index != i {
-            input.swap(i, min_index);
+            swap_at(input, 0, i, min_index, observer);
         }
     }
 }
 
-// An in-place quicksort implementation using Lomuto partition scheme
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+// An in-place quicksort implementation using three-way (Dutch national flag)
+// partitioning around a random pivot: elements equal to the pivot end up in the
+// middle and are excluded from further partitions, so inputs with few distinct
+// values are sorted in few passes. Short partitions are finished with insertion sort.
 // This implementation uses a stack to avoid recursion
-pub fn quick_sort<T: Ord + Copy, R: rand::Rng>(input: InPlaceInput<T>, rng: &mut R) {
+pub fn quick_sort<T: Ord, R: rand::Rng>(input: InPlaceInput<T>, rng: &mut R) {
+    quick_sort_by(input, rng, &mut T::cmp);
+}
+
+pub fn quick_sort_by_key<T, R: rand::Rng, K: Ord, F>(input: InPlaceInput<T>, rng: &mut R, f: &mut F)
+where
+    F: FnMut(&T) -> K,
+{
+    quick_sort_by(input, rng, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn quick_sort_by<T, R: rand::Rng, F>(input: InPlaceInput<T>, rng: &mut R, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    quick_sort_by_observed(input, rng, compare, &mut ());
+}
+
+// Quicksort reporting its operations to `observer`.
+pub fn quick_sort_observed<T: Ord, R: rand::Rng, O: SortObserver>(
+    input: InPlaceInput<T>,
+    rng: &mut R,
+    observer: &mut O,
+) {
+    quick_sort_by_observed(input, rng, &mut T::cmp, observer);
+}
+
+pub fn quick_sort_by_observed<T, R: rand::Rng, F, O>(
+    input: InPlaceInput<T>,
+    rng: &mut R,
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
     let mut partitions: Vec<(usize, usize)> = vec![(0, input.len())];
 
     while let Some((left, right)) = partitions.pop() {
         let partition = &mut input[left..right];
-        if partition.len() <= 5 {
-            merge_sort(partition);
+        if partition.len() <= INSERTION_SORT_THRESHOLD {
+            insertion_sort_observed(partition, left, compare, observer);
             continue;
         }
 
-        let Some(pivot) = partition.choose(rng).cloned() else {
-            // Could not choose a pivot, likely because the partition is empty
+        let pivot_index = rng.random_range(0..partition.len());
+        let (less_end, greater_start) =
+            three_way_partition_observed(partition, left, pivot_index, compare, observer);
+
+        if less_end > 1 {
+            partitions.push((left, left + less_end));
+        }
+        if partition.len() - greater_start > 1 {
+            partitions.push((left + greater_start, right));
+        }
+    }
+}
+
+// The quicksort with Lomuto partitioning which sends each element equal to the
+// pivot to a random side with a coin flip. Kept as a baseline for benchmarks.
+pub fn lomuto_quick_sort<T: Ord, R: rand::Rng>(input: InPlaceInput<T>, rng: &mut R) {
+    lomuto_quick_sort_by(input, rng, &mut T::cmp);
+}
+
+pub fn lomuto_quick_sort_by<T, R: rand::Rng, F>(
+    input: InPlaceInput<T>,
+    rng: &mut R,
+    compare: &mut F,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut partitions: Vec<(usize, usize)> = vec![(0, input.len())];
+
+    while let Some((left, right)) = partitions.pop() {
+        let partition = &mut input[left..right];
+        if partition.len() <= 5 {
+            merge_sort_by(partition, compare);
             continue;
-        };
-
-        // Lomuto partitioning
-        let mut after_less_index = 0;
-        for i in 0..partition.len() {
-            let cmp = partition[i].cmp(&pivot);
-            if matches!(cmp, std::cmp::Ordering::Less)
-                || (matches!(cmp, std::cmp::Ordering::Equal) && rng.random())
-            {
-                partition.swap(i, after_less_index);
-                after_less_index += 1;
-            }
         }
+
+        let pivot_index = rng.random_range(0..partition.len());
+        let after_less_index = lomuto_partition(partition, pivot_index, &mut |value, pivot| {
+            let cmp = compare(value, pivot);
+            matches!(cmp, Ordering::Less) || (matches!(cmp, Ordering::Equal) && rng.random())
+        });
+
         if after_less_index > 1 {
             partitions.push((left, left + after_less_index));
         }
-        if partition.len() - after_less_index > 1 {
-            partitions.push((left + after_less_index, left + partition.len()));
+        if partition.len() - after_less_index > 2 {
+            partitions.push((left + after_less_index + 1, left + partition.len()));
+        }
+    }
+}
+
+// Three-way partitioning around the element at `pivot_index`.
+// Returns `(less_end, greater_start)`: afterwards partition[..less_end] is less
+// than the pivot, partition[less_end..greater_start] equal to it and
+// partition[greater_start..] greater.
+fn three_way_partition<T, F>(
+    partition: InPlaceInput<T>,
+    pivot_index: usize,
+    compare: &mut F,
+) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    three_way_partition_observed(partition, 0, pivot_index, compare, &mut ())
+}
+
+// `offset` is the position of the partition in the input, for the observer.
+fn three_way_partition_observed<T, F, O>(
+    partition: InPlaceInput<T>,
+    offset: usize,
+    pivot_index: usize,
+    compare: &mut F,
+    observer: &mut O,
+) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    // The pivot is parked at the front while the rest is partitioned
+    if pivot_index != 0 {
+        swap_at(partition, offset, 0, pivot_index, observer);
+    }
+
+    let mut less_end = 1;
+    let mut index = 1;
+    let mut greater_start = partition.len();
+    while index < greater_start {
+        match compare_at(partition, offset, index, 0, compare, observer) {
+            Ordering::Less => {
+                if index != less_end {
+                    swap_at(partition, offset, index, less_end, observer);
+                }
+                less_end += 1;
+                index += 1;
+            }
+            Ordering::Equal => index += 1,
+            Ordering::Greater => {
+                greater_start -= 1;
+                if index != greater_start {
+                    swap_at(partition, offset, index, greater_start, observer);
+                }
+            }
+        }
+    }
+
+    // Put the pivot right before the other elements equal to it
+    if less_end > 1 {
+        swap_at(partition, offset, 0, less_end - 1, observer);
+    }
+    (less_end - 1, greater_start)
+}
+
+// Lomuto partitioning around the element at `pivot_index`.
+// Elements for which `goes_left(element, pivot)` holds are moved before the pivot,
+// the rest after it. Returns the final position of the pivot.
+fn lomuto_partition<T, P>(
+    partition: InPlaceInput<T>,
+    pivot_index: usize,
+    goes_left: &mut P,
+) -> usize
+where
+    P: FnMut(&T, &T) -> bool,
+{
+    // The pivot is moved to the end of the partition instead of being copied out
+    let last_index = partition.len() - 1;
+    partition.swap(pivot_index, last_index);
+    let (rest, pivot) = partition.split_at_mut(last_index);
+    let pivot = &pivot[0];
+
+    let mut after_less_index = 0;
+    for i in 0..rest.len() {
+        if goes_left(&rest[i], pivot) {
+            rest.swap(i, after_less_index);
+            after_less_index += 1;
         }
     }
+    partition.swap(after_less_index, last_index);
+    after_less_index
+}
+
+const NINTHER_THRESHOLD: usize = 128;
+
+// Introsort: a quicksort with a deterministic pivot (median of three, or
+// Tukey's ninther for large partitions) which switches a partition to heap sort
+// once it is nested deeper than 2 * log2(n), so no input can make it quadratic.
+// Short partitions are finished with insertion sort.
+// Complexity:
+// Time: O(n log n) in the worst case
+// Space: O(log n) for the stack of partitions
+pub fn intro_sort<T: Ord>(input: InPlaceInput<T>) {
+    intro_sort_by(input, &mut T::cmp);
+}
+
+pub fn intro_sort_by_key<T, K: Ord, F>(input: InPlaceInput<T>, f: &mut F)
+where
+    F: FnMut(&T) -> K,
+{
+    intro_sort_by(input, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn intro_sort_by<T, F>(input: InPlaceInput<T>, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let depth_limit = 2 * input.len().checked_ilog2().unwrap_or(0);
+    let mut partitions: Vec<(usize, usize, u32)> = vec![(0, input.len(), 0)];
+
+    while let Some((left, right, depth)) = partitions.pop() {
+        let partition = &mut input[left..right];
+        if partition.len() <= INSERTION_SORT_THRESHOLD {
+            insertion_sort_by(partition, compare);
+            continue;
+        }
+        if depth > depth_limit {
+            heap_sort_by(partition, compare);
+            continue;
+        }
+
+        let pivot_index = choose_pivot(partition, compare);
+        let (less_end, greater_start) = three_way_partition(partition, pivot_index, compare);
+
+        if less_end > 1 {
+            partitions.push((left, left + less_end, depth + 1));
+        }
+        if partition.len() - greater_start > 1 {
+            partitions.push((left + greater_start, right, depth + 1));
+        }
+    }
+}
+
+fn choose_pivot<T, F>(input: &[T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = input.len();
+    let (first, middle, last) = (0, len / 2, len - 1);
+    if len < NINTHER_THRESHOLD {
+        return median_of_three(input, first, middle, last, compare);
+    }
+
+    let step = len / 8;
+    let first = median_of_three(input, first, first + step, first + 2 * step, compare);
+    let middle = median_of_three(input, middle - step, middle, middle + step, compare);
+    let last = median_of_three(input, last - 2 * step, last - step, last, compare);
+    median_of_three(input, first, middle, last, compare)
+}
+
+fn median_of_three<T, F>(input: &[T], a: usize, b: usize, c: usize, compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let a_less_than_b = compare(&input[a], &input[b]) == Ordering::Less;
+    let b_less_than_c = compare(&input[b], &input[c]) == Ordering::Less;
+    if a_less_than_b == b_less_than_c {
+        return b;
+    }
+    let a_less_than_c = compare(&input[a], &input[c]) == Ordering::Less;
+    if a_less_than_b == a_less_than_c { c } else { a }
 }
 
 // In-place merge sort implementation
-pub fn merge_sort<T: Ord + Copy>(input: InPlaceInput<T>) {
+// It is not stable: the part of the input used as a merge buffer gets reordered
+pub fn merge_sort<T: Ord>(input: InPlaceInput<T>) {
+    merge_sort_by(input, &mut T::cmp);
+}
+
+pub fn merge_sort_by_key<T, K: Ord, F>(input: InPlaceInput<T>, f: &mut F)
+where
+    F: FnMut(&T) -> K,
+{
+    merge_sort_by(input, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn merge_sort_by<T, F>(input: InPlaceInput<T>, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    merge_sort_by_observed(input, compare, &mut ());
+}
+
+// Merge sort reporting its operations to `observer`.
+pub fn merge_sort_observed<T: Ord, O: SortObserver>(input: InPlaceInput<T>, observer: &mut O) {
+    merge_sort_by_observed(input, &mut T::cmp, observer);
+}
+
+pub fn merge_sort_by_observed<T, F, O>(input: InPlaceInput<T>, compare: &mut F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
     let input_len = input.len();
     let first_half_len = input.len() / 2 + input.len() % 2;
-    let (first_half, second_half) = input.split_at_mut(first_half_len);
-    merge_sort_into_buffer(second_half, first_half);
+    merge_sort_into_buffer(input, first_half_len..input_len, 0, compare, observer);
 
     let mut reminder = first_half_len;
     while reminder > 1 {
-        let (first_part, rest) = input.split_at_mut(reminder / 2);
-        let first_part_len = first_part.len();
-        merge_sort_into_buffer(first_part, rest);
+        let first_part_len = reminder / 2;
+        merge_sort_into_buffer(input, 0..first_part_len, first_part_len, compare, observer);
 
         let mut first_part_index = 0;
         let mut second_part_index = reminder;
@@ -73,12 +396,19 @@ pub fn merge_sort<T: Ord + Copy>(input: InPlaceInput<T>) {
         for i in free_index..input.len() {
             let take_from_the_first_part = second_part_index == input.len()
                 || (first_part_index < first_part_len
-                    && input[first_part_index] < input[second_part_index]);
+                    && compare_at(
+                        input,
+                        0,
+                        first_part_index,
+                        second_part_index,
+                        compare,
+                        observer,
+                    ) == Ordering::Less);
             if take_from_the_first_part {
-                input.swap(i, first_part_index);
+                swap_at(input, 0, i, first_part_index, observer);
                 first_part_index += 1;
             } else {
-                input.swap(i, second_part_index);
+                swap_at(input, 0, i, second_part_index, observer);
                 second_part_index += 1;
             }
         }
@@ -87,8 +417,8 @@ pub fn merge_sort<T: Ord + Copy>(input: InPlaceInput<T>) {
 
     if reminder == 1 && input_len > 1 {
         for i in 1..input_len {
-            if input[i - 1] > input[i] {
-                input.swap(i, i - 1);
+            if compare_at(input, 0, i - 1, i, compare, observer) == Ordering::Greater {
+                swap_at(input, 0, i, i - 1, observer);
             } else {
                 break;
             }
@@ -96,50 +426,407 @@ pub fn merge_sort<T: Ord + Copy>(input: InPlaceInput<T>) {
     }
 }
 
-fn merge_sort_into_buffer<T: Ord + Copy>(input: InPlaceInput<T>, buffer: InPlaceInput<T>) {
-    let len = input.len();
+// Sorts input[range] using the elements from `buffer_start` on as a scratch
+// area, which must not overlap the range and gets permuted.
+fn merge_sort_into_buffer<T, F, O>(
+    input: InPlaceInput<T>,
+    range: Range<usize>,
+    buffer_start: usize,
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let len = range.len();
     for width in (1..).map(|w| 1 << w).take_while(|w| *w / 2 < len) {
         for start in (0..len).step_by(width) {
-            let end = (start + width).min(input.len());
+            let end = (start + width).min(len);
             if end - start < width / 2 {
                 continue;
             }
 
-            let (lhs, rhs) = input[start..end].split_at_mut(width / 2);
-            merge_sorted_into_buffer(lhs, rhs, buffer);
+            let (start, mid, end) = (
+                range.start + start,
+                range.start + start + width / 2,
+                range.start + end,
+            );
+            merge_sorted_into_buffer(input, start..mid, mid..end, buffer_start, compare, observer);
             for i in 0..(end - start) {
-                std::mem::swap(&mut input[start + i], &mut buffer[i]);
+                swap_at(input, 0, start + i, buffer_start + i, observer);
             }
         }
     }
 }
 
-fn merge_sorted_into_buffer<T: Ord + Copy>(
-    lhs: InPlaceInput<T>,
-    rhs: InPlaceInput<T>,
-    buffer: InPlaceInput<T>,
-) {
-    let mut lhs_index = 0;
-    let mut rhs_index = 0;
-    let mut buffer_index = 0;
+// Merges the sorted input[lhs] and input[rhs] into the input from `buffer_start`
+// on, swapping the buffer elements into their place.
+fn merge_sorted_into_buffer<T, F, O>(
+    input: InPlaceInput<T>,
+    lhs: Range<usize>,
+    rhs: Range<usize>,
+    buffer_start: usize,
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let mut lhs_index = lhs.start;
+    let mut rhs_index = rhs.start;
+    let mut buffer_index = buffer_start;
 
-    while lhs_index < lhs.len() || rhs_index < rhs.len() {
-        let take_from_lhs =
-            rhs_index == rhs.len() || (lhs_index < lhs.len() && lhs[lhs_index] < rhs[rhs_index]);
+    while lhs_index < lhs.end || rhs_index < rhs.end {
+        let take_from_lhs = rhs_index == rhs.end
+            || (lhs_index < lhs.end
+                && compare_at(input, 0, lhs_index, rhs_index, compare, observer) == Ordering::Less);
         if take_from_lhs {
-            std::mem::swap(&mut buffer[buffer_index], &mut lhs[lhs_index]);
+            swap_at(input, 0, buffer_index, lhs_index, observer);
             lhs_index += 1;
         } else {
-            std::mem::swap(&mut buffer[buffer_index], &mut rhs[rhs_index]);
+            swap_at(input, 0, buffer_index, rhs_index, observer);
             rhs_index += 1;
         }
         buffer_index += 1;
     }
 }
 
+const STABLE_MERGE_SORT_RUN: usize = 16;
+
+// Stable in-place merge sort: equal elements keep their original relative order.
+// Short runs are sorted by insertion and then merged bottom-up by rotations,
+// so no buffer is needed.
+// Complexity:
+// Time: O(n log^2 n)
+// Space: O(log n) for the recursion of the merge
+pub fn stable_merge_sort<T: Ord>(input: InPlaceInput<T>) {
+    stable_merge_sort_by(input, &mut T::cmp);
+}
+
+pub fn stable_merge_sort_by_key<T, K: Ord, F>(input: InPlaceInput<T>, f: &mut F)
+where
+    F: FnMut(&T) -> K,
+{
+    stable_merge_sort_by(input, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn stable_merge_sort_by<T, F>(input: InPlaceInput<T>, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = input.len();
+    for run in input.chunks_mut(STABLE_MERGE_SORT_RUN) {
+        insertion_sort_by(run, compare);
+    }
+
+    let mut width = STABLE_MERGE_SORT_RUN;
+    while width < len {
+        for start in (0..len).step_by(2 * width) {
+            let end = (start + 2 * width).min(len);
+            if end - start > width {
+                merge_by_rotation(&mut input[start..end], width, compare);
+            }
+        }
+        width *= 2;
+    }
+}
+
+// Merges the sorted input[..mid] and input[mid..] in place.
+// The longer part is cut in half, the matching cut of the other part is found
+// by binary search, and the two middle pieces are swapped by a rotation, which
+// leaves two independent smaller merges. Ties always resolve to the left part.
+fn merge_by_rotation<T, F>(input: InPlaceInput<T>, mid: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = input.len();
+    if mid == 0 || mid == len || compare(&input[mid - 1], &input[mid]) != Ordering::Greater {
+        return;
+    }
+    if len == 2 {
+        input.swap(0, 1);
+        return;
+    }
+
+    let (split, first_mid, second_mid) = split_merge_by_rotation(input, mid, compare);
+    let (first, second) = input.split_at_mut(split);
+    merge_by_rotation(first, first_mid, compare);
+    merge_by_rotation(second, second_mid, compare);
+}
+
+// One step of `merge_by_rotation`, for 0 < mid < input.len(): returns `split`
+// and the midpoints of the merges left in input[..split] and input[split..].
+fn split_merge_by_rotation<T, F>(
+    input: InPlaceInput<T>,
+    mid: usize,
+    compare: &mut F,
+) -> (usize, usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let (lhs, rhs) = input.split_at(mid);
+    let (lhs_cut, rhs_cut) = if lhs.len() >= rhs.len() {
+        let lhs_cut = lhs.len() / 2;
+        // Right elements equal to the cut element must stay after it
+        let rhs_cut = partition_point(rhs, |value| compare(value, &lhs[lhs_cut]) == Ordering::Less);
+        (lhs_cut, rhs_cut)
+    } else {
+        let rhs_cut = rhs.len() / 2;
+        // Left elements equal to the cut element must stay before it
+        let lhs_cut = partition_point(lhs, |value| {
+            compare(value, &rhs[rhs_cut]) != Ordering::Greater
+        });
+        (lhs_cut, rhs_cut)
+    };
+
+    input[lhs_cut..mid + rhs_cut].rotate_left(mid - lhs_cut);
+    (lhs_cut + rhs_cut, lhs_cut, mid - lhs_cut)
+}
+
+// In-place heap sort: builds a max-heap and repeatedly moves its root to the end.
+// Complexity:
+// Time: O(n log n) in the worst case
+// Space: O(1)
+pub fn heap_sort<T: Ord>(input: InPlaceInput<T>) {
+    heap_sort_by(input, &mut T::cmp);
+}
+
+pub fn heap_sort_by_key<T, K: Ord, F>(input: InPlaceInput<T>, f: &mut F)
+where
+    F: FnMut(&T) -> K,
+{
+    heap_sort_by(input, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn heap_sort_by<T, F>(input: InPlaceInput<T>, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = input.len();
+    for node in (0..len / 2).rev() {
+        sift_down_by(input, node, compare);
+    }
+    for end in (1..len).rev() {
+        input.swap(0, end);
+        sift_down_by(&mut input[..end], 0, compare);
+    }
+}
+
+// Restores the max-heap property for the subtree rooted at `node`
+fn sift_down_by<T, F>(heap: InPlaceInput<T>, mut node: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        let mut child = 2 * node + 1;
+        if child >= heap.len() {
+            break;
+        }
+        if child + 1 < heap.len() && compare(&heap[child], &heap[child + 1]) == Ordering::Less {
+            child += 1;
+        }
+        if compare(&heap[node], &heap[child]) != Ordering::Less {
+            break;
+        }
+        heap.swap(node, child);
+        node = child;
+    }
+}
+
+// Stable insertion sort, the fastest option for short or nearly sorted inputs.
+// Complexity:
+// Time: O(n^2), O(n) for sorted input
+// Space: O(1)
+pub fn insertion_sort<T: Ord>(input: InPlaceInput<T>) {
+    insertion_sort_by(input, &mut T::cmp);
+}
+
+pub fn insertion_sort_by_key<T, K: Ord, F>(input: InPlaceInput<T>, f: &mut F)
+where
+    F: FnMut(&T) -> K,
+{
+    insertion_sort_by(input, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn insertion_sort_by<T, F>(input: InPlaceInput<T>, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    insertion_sort_observed(input, 0, compare, &mut ());
+}
+
+// `offset` is the position of `input` in the slice being sorted, for the observer.
+fn insertion_sort_observed<T, F, O>(
+    input: InPlaceInput<T>,
+    offset: usize,
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    for i in 1..input.len() {
+        let mut j = i;
+        while j > 0 && compare_at(input, offset, j - 1, j, compare, observer) == Ordering::Greater {
+            swap_at(input, offset, j - 1, j, observer);
+            j -= 1;
+        }
+    }
+}
+
+// Stable insertion sort which finds the place of each element by binary search,
+// so it makes O(n log n) comparisons while still moving O(n^2) elements.
+// Complexity:
+// Time: O(n^2)
+// Space: O(1)
+pub fn binary_insertion_sort<T: Ord>(input: InPlaceInput<T>) {
+    binary_insertion_sort_by(input, &mut T::cmp);
+}
+
+pub fn binary_insertion_sort_by_key<T, K: Ord, F>(input: InPlaceInput<T>, f: &mut F)
+where
+    F: FnMut(&T) -> K,
+{
+    binary_insertion_sort_by(input, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn binary_insertion_sort_by<T, F>(input: InPlaceInput<T>, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    binary_insertion_sort_from(input, 1, compare);
+}
+
+// Binary insertion sort of an input whose first `sorted_len` elements are already sorted
+fn binary_insertion_sort_from<T, F>(input: InPlaceInput<T>, sorted_len: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in sorted_len.max(1)..input.len() {
+        let (sorted, rest) = input.split_at(i);
+        // Inserting after the equal elements keeps the sort stable
+        let position = partition_point(sorted, |value| {
+            compare(value, &rest[0]) != Ordering::Greater
+        });
+        input[position..=i].rotate_right(1);
+    }
+}
+
+// Gap sequences for the shell sort
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShellGaps {
+    // 1, 4, 10, 23, 57, 132, 301, 701, 1750, continued by multiplying by 2.25
+    #[default]
+    Ciura,
+    // 1, 8, 23, 77, 281, ...: 4^k + 3 * 2^(k - 1) + 1
+    Sedgewick,
+    // 1, 4, 9, 20, 46, 103, ...: ceil(h_k) where h_k = 2.25 * h_(k - 1) + 1
+    Tokuda,
+}
+
+impl ShellGaps {
+    // The gaps below `len` in decreasing order, always ending with 1
+    fn gaps(self, len: usize) -> Vec<usize> {
+        let mut gaps = vec![1];
+        match self {
+            ShellGaps::Ciura => {
+                const CIURA: [usize; 9] = [1, 4, 10, 23, 57, 132, 301, 701, 1750];
+                gaps = CIURA.iter().copied().take_while(|&gap| gap < len).collect();
+                if gaps.len() == CIURA.len() {
+                    while let Some(next) = gaps[gaps.len() - 1]
+                        .checked_mul(9)
+                        .map(|gap| gap / 4)
+                        .filter(|&gap| gap < len)
+                    {
+                        gaps.push(next);
+                    }
+                }
+            }
+            ShellGaps::Sedgewick => {
+                for k in 1.. {
+                    let gap = 4usize
+                        .checked_pow(k)
+                        .and_then(|power| power.checked_add(3 * (1 << (k - 1)) + 1));
+                    match gap {
+                        Some(gap) if gap < len => gaps.push(gap),
+                        _ => break,
+                    }
+                }
+            }
+            ShellGaps::Tokuda => {
+                let mut h = 1.0f64;
+                loop {
+                    h = 2.25 * h + 1.0;
+                    let gap = h.ceil();
+                    if gap >= len as f64 {
+                        break;
+                    }
+                    gaps.push(gap as usize);
+                }
+            }
+        }
+        if gaps.is_empty() {
+            gaps.push(1);
+        }
+        gaps.reverse();
+        gaps
+    }
+}
+
+// In-place shell sort: insertion sorts over elements `gap` apart for a
+// decreasing sequence of gaps ending with 1.
+// Complexity depends on the gap sequence, for Sedgewick's it is O(n^(4/3)).
+// Space: O(1) besides the gap sequence
+pub fn shell_sort<T: Ord>(input: InPlaceInput<T>, gaps: ShellGaps) {
+    shell_sort_by(input, gaps, &mut T::cmp);
+}
+
+pub fn shell_sort_by_key<T, K: Ord, F>(input: InPlaceInput<T>, gaps: ShellGaps, f: &mut F)
+where
+    F: FnMut(&T) -> K,
+{
+    shell_sort_by(input, gaps, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn shell_sort_by<T, F>(input: InPlaceInput<T>, gaps: ShellGaps, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for gap in gaps.gaps(input.len()) {
+        for i in gap..input.len() {
+            let mut j = i;
+            while j >= gap && compare(&input[j - gap], &input[j]) == Ordering::Greater {
+                input.swap(j - gap, j);
+                j -= gap;
+            }
+        }
+    }
+}
+
+// Reorders the input so that input[i] ends up holding the element which was at
+// order[i], following the cycles of the permutation with swaps.
+// `order` must be a permutation of 0..input.len(), it is overwritten.
+fn apply_permutation<T>(input: InPlaceInput<T>, order: &mut [usize]) {
+    const VISITED: usize = usize::MAX;
+    for start in 0..input.len() {
+        let mut current = start;
+        while order[current] != VISITED {
+            let source = order[current];
+            order[current] = VISITED;
+            if source == start {
+                break;
+            }
+            input.swap(current, source);
+            current = source;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generators::{Distribution, generate};
+    use rand::Rng;
     use rand::seq::SliceRandom;
 
     #[test]
@@ -198,6 +885,154 @@ mod tests {
         assert_eq!(arr2, ["elephant", "lion", "tiger", "zebra"]);
     }
 
+    #[test]
+    fn test_lomuto_quick_sort() {
+        let mut rng = rand::rng();
+        let mut arr = [1, 1, 1, 1, 1, 1, 1, 1, 1, 1];
+        lomuto_quick_sort(&mut arr, &mut rng);
+        assert_eq!(arr, [1, 1, 1, 1, 1, 1, 1, 1, 1, 1]);
+
+        let mut arr2: Vec<i32> = (0..500).map(|i| i % 7).collect();
+        arr2.shuffle(&mut rng);
+        let mut expected = arr2.clone();
+        expected.sort();
+        lomuto_quick_sort(&mut arr2, &mut rng);
+        assert_eq!(arr2, expected);
+    }
+
+    #[test]
+    fn test_three_way_partition() {
+        let mut arr = [3, 5, 1, 3, 7, 3, 0, 9, 3];
+        let (less_end, greater_start) = three_way_partition(&mut arr, 3, &mut i32::cmp);
+        assert_eq!((less_end, greater_start), (2, 6));
+        assert!(arr[..2].iter().all(|&value| value < 3));
+        assert_eq!(arr[2..6], [3, 3, 3, 3]);
+        assert!(arr[6..].iter().all(|&value| value > 3));
+
+        let mut arr2 = [4, 4, 4];
+        assert_eq!(three_way_partition(&mut arr2, 1, &mut i32::cmp), (0, 3));
+
+        let mut arr3 = [2, 8, 5];
+        assert_eq!(three_way_partition(&mut arr3, 0, &mut i32::cmp), (0, 1));
+        assert_eq!(arr3[0], 2);
+    }
+
+    #[test]
+    fn test_quick_sort_few_distinct_values() {
+        let mut rng = rand::rng();
+        for distinct in [1, 2, 3, 10] {
+            let mut arr: Vec<i32> = (0..2000).map(|i| i % distinct).collect();
+            arr.shuffle(&mut rng);
+            let mut expected = arr.clone();
+            expected.sort();
+            quick_sort(&mut arr, &mut rng);
+            assert_eq!(arr, expected);
+        }
+    }
+
+    #[test]
+    fn test_intro_sort() {
+        let mut arr = [64, 25, 12, 22, 11];
+        intro_sort(&mut arr);
+        assert_eq!(arr, [11, 12, 22, 25, 64]);
+
+        let mut arr2: [i32; 0] = [];
+        intro_sort(&mut arr2);
+        assert_eq!(arr2, []);
+
+        let mut arr3 = [1, 1, 1, 1, 1, 1, 1, 1, 1, 1];
+        intro_sort(&mut arr3);
+        assert_eq!(arr3, [1, 1, 1, 1, 1, 1, 1, 1, 1, 1]);
+
+        let mut arr4: Vec<i32> = (0..1001).collect();
+        arr4.shuffle(&mut rand::rng());
+        intro_sort(&mut arr4);
+        assert_eq!(arr4, (0..1001).collect::<Vec<i32>>());
+
+        let mut arr5 = ["banana", "fig", "apple", "date"];
+        intro_sort_by_key(&mut arr5, &mut |word| word.len());
+        assert_eq!(arr5, ["fig", "date", "apple", "banana"]);
+    }
+
+    #[test]
+    fn test_intro_sort_worst_case_comparisons() {
+        let len: usize = 1 << 14;
+        let bound = 4 * len * len.ilog2() as usize;
+        let half = len as i32 / 2;
+        let inputs: [Vec<i32>; 5] = [
+            (0..len as i32).collect(),
+            (0..len as i32).rev().collect(),
+            vec![7; len],
+            // Organ pipe
+            (0..half).chain((0..half).rev()).collect(),
+            // Few distinct values in a repeating pattern
+            (0..len as i32).map(|i| i % 3).collect(),
+        ];
+        for mut input in inputs {
+            let mut expected = input.clone();
+            expected.sort();
+            let mut comparisons = 0;
+            intro_sort_by(&mut input, &mut |lhs, rhs| {
+                comparisons += 1;
+                lhs.cmp(rhs)
+            });
+            assert_eq!(input, expected);
+            assert!(
+                comparisons <= bound,
+                "{comparisons} comparisons exceed {bound}"
+            );
+        }
+    }
+
+    // Sorting must only move elements around, never duplicate them
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct PanicsOnClone(i32);
+
+    impl Clone for PanicsOnClone {
+        fn clone(&self) -> Self {
+            panic!("element {} was cloned", self.0)
+        }
+    }
+
+    #[test]
+    fn test_owned_elements() {
+        let mut rng = rand::rng();
+        let words = [
+            "pear", "fig", "banana", "apple", "kiwi", "cherry", "date", "fig",
+        ];
+        let mut expected: Vec<String> = words.iter().map(|word| word.to_string()).collect();
+        expected.sort();
+
+        let mut arr: Vec<String> = words.iter().map(|word| word.to_string()).collect();
+        quick_sort(&mut arr, &mut rng);
+        assert_eq!(arr, expected);
+
+        let mut arr: Vec<String> = words.iter().map(|word| word.to_string()).collect();
+        merge_sort(&mut arr);
+        assert_eq!(arr, expected);
+
+        let mut arr: Vec<Vec<u8>> = vec![vec![3, 1], vec![], vec![3], vec![0, 9, 9], vec![3, 0]];
+        merge_sort(&mut arr);
+        assert_eq!(
+            arr,
+            [vec![], vec![0, 9, 9], vec![3], vec![3, 0], vec![3, 1]]
+        );
+
+        let mut values: Vec<i32> = (0..200).map(|i| i % 37).collect();
+        values.shuffle(&mut rng);
+        let mut expected = values.clone();
+        expected.sort();
+        let expected: Vec<PanicsOnClone> = expected.into_iter().map(PanicsOnClone).collect();
+
+        let mut arr: Vec<PanicsOnClone> = values.iter().copied().map(PanicsOnClone).collect();
+        quick_sort(&mut arr, &mut rng);
+        assert_eq!(arr, expected);
+
+        let mut arr: Vec<PanicsOnClone> = values.iter().copied().map(PanicsOnClone).collect();
+        merge_sort(&mut arr);
+        assert_eq!(arr, expected);
+    }
+
     #[test]
     fn test_merge_sort() {
         let mut arr = [64, 25, 12, 22, 11];
@@ -237,20 +1072,216 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_by_and_by_key() {
+        let mut rng = rand::rng();
+        let sorts: [fn(&mut [i32], &mut rand::rngs::ThreadRng); 3] = [
+            |input, _| selection_sort_by(input, &mut |lhs, rhs| rhs.cmp(lhs)),
+            |input, rng| quick_sort_by(input, rng, &mut |lhs, rhs| rhs.cmp(lhs)),
+            |input, _| merge_sort_by(input, &mut |lhs, rhs| rhs.cmp(lhs)),
+        ];
+        for sort in sorts {
+            let mut arr: Vec<i32> = (0..100).collect();
+            arr.shuffle(&mut rng);
+            sort(&mut arr, &mut rng);
+            assert_eq!(arr, (0..100).rev().collect::<Vec<i32>>());
+        }
+
+        let mut floats = [2.5, -1.0, f64::INFINITY, 0.0, -0.0, 1e-9, -7.25];
+        let expected = [-7.25, -1.0, -0.0, 0.0, 1e-9, 2.5, f64::INFINITY];
+        let mut arr = floats;
+        selection_sort_by(&mut arr, &mut f64::total_cmp);
+        assert_eq!(arr, expected);
+        let mut arr = floats;
+        quick_sort_by(&mut arr, &mut rng, &mut f64::total_cmp);
+        assert_eq!(arr, expected);
+        merge_sort_by(&mut floats, &mut f64::total_cmp);
+        assert_eq!(floats, expected);
+
+        let records = [("carol", 35), ("alice", 30), ("bob", 25), ("dave", 40)];
+        let expected = [("bob", 25), ("alice", 30), ("carol", 35), ("dave", 40)];
+        let mut arr = records;
+        selection_sort_by_key(&mut arr, &mut |record| record.1);
+        assert_eq!(arr, expected);
+        let mut arr = records;
+        quick_sort_by_key(&mut arr, &mut rng, &mut |record| record.1);
+        assert_eq!(arr, expected);
+        let mut arr = records;
+        merge_sort_by_key(&mut arr, &mut |record| record.1);
+        assert_eq!(arr, expected);
+    }
+
+    // Sorts elements tagged with their original index by the key only and
+    // checks that elements with equal keys kept the order of their indices
+    pub(super) fn assert_stable<S: FnMut(&mut [(u8, usize)])>(mut sort: S) {
+        let mut rng = rand::rng();
+        for len in [0, 1, 2, 3, 15, 16, 17, 31, 33, 100, 257, 1000] {
+            for distinct_keys in [1, 2, 5, 50] {
+                let mut input: Vec<(u8, usize)> = (0..len)
+                    .map(|index| (rng.random_range(0..distinct_keys), index))
+                    .collect();
+                sort(&mut input);
+                for pair in input.windows(2) {
+                    assert!(
+                        pair[0] < pair[1],
+                        "{:?} is out of order or unstable for len {len}",
+                        pair
+                    );
+                }
+                let mut indices: Vec<usize> = input.iter().map(|&(_, index)| index).collect();
+                indices.sort();
+                assert_eq!(indices, (0..len).collect::<Vec<usize>>());
+            }
+        }
+    }
+
+    #[test]
+    fn test_stable_merge_sort() {
+        let mut arr = [64, 25, 12, 22, 11];
+        stable_merge_sort(&mut arr);
+        assert_eq!(arr, [11, 12, 22, 25, 64]);
+
+        let mut arr2: [i32; 0] = [];
+        stable_merge_sort(&mut arr2);
+        assert_eq!(arr2, []);
+
+        let mut arr3: Vec<i32> = (0..501).collect();
+        arr3.shuffle(&mut rand::rng());
+        stable_merge_sort(&mut arr3);
+        assert_eq!(arr3, (0..501).collect::<Vec<i32>>());
+
+        assert_stable(|input| stable_merge_sort_by_key(input, &mut |element| element.0));
+        assert_stable(|input| {
+            stable_merge_sort_by(input, &mut |lhs, rhs| lhs.0.cmp(&rhs.0));
+        });
+    }
+
+    #[test]
+    fn test_merge_by_rotation() {
+        let mut arr = [
+            (1, 'a'),
+            (3, 'b'),
+            (3, 'c'),
+            (5, 'd'),
+            (0, 'e'),
+            (3, 'f'),
+            (9, 'g'),
+        ];
+        merge_by_rotation(&mut arr, 4, &mut |lhs, rhs| lhs.0.cmp(&rhs.0));
+        assert_eq!(
+            arr,
+            [
+                (0, 'e'),
+                (1, 'a'),
+                (3, 'b'),
+                (3, 'c'),
+                (3, 'f'),
+                (5, 'd'),
+                (9, 'g')
+            ]
+        );
+    }
+
+    // Runs a sort over a common set of inputs and compares it with `slice::sort`
+    pub(super) fn check_sort_battery<S: FnMut(&mut [i32])>(mut sort: S) {
+        let mut rng = rand::rng();
+        let mut inputs: Vec<Vec<i32>> = vec![
+            vec![],
+            vec![1],
+            vec![2, 1],
+            vec![1, 2],
+            vec![64, 25, 12, 22, 11],
+            vec![7; 40],
+            (0..300).collect(),
+            (0..300).rev().collect(),
+            (0..150).chain((0..150).rev()).collect(),
+            (0..300).map(|i| i % 10).collect(),
+            vec![i32::MAX, i32::MIN, 0, -1, 1],
+        ];
+        for len in [3, 6, 17, 100, 1000] {
+            let mut shuffled: Vec<i32> = (0..len).collect();
+            shuffled.shuffle(&mut rng);
+            inputs.push(shuffled);
+            inputs.push((0..len).map(|_| rng.random_range(0..4)).collect());
+        }
+        for distribution in Distribution::ALL {
+            inputs.push(generate(distribution, 513, &mut rng));
+        }
+
+        for mut input in inputs {
+            let mut expected = input.clone();
+            expected.sort();
+            sort(&mut input);
+            assert_eq!(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_battery() {
+        let mut rng = rand::rng();
+        check_sort_battery(selection_sort);
+        check_sort_battery(|input| quick_sort(input, &mut rng));
+        check_sort_battery(|input| lomuto_quick_sort(input, &mut rng));
+        check_sort_battery(intro_sort);
+        check_sort_battery(merge_sort);
+        check_sort_battery(stable_merge_sort);
+        check_sort_battery(heap_sort);
+        check_sort_battery(insertion_sort);
+        check_sort_battery(binary_insertion_sort);
+        for gaps in [ShellGaps::Ciura, ShellGaps::Sedgewick, ShellGaps::Tokuda] {
+            check_sort_battery(|input| shell_sort(input, gaps));
+        }
+    }
+
+    #[test]
+    fn test_insertion_sorts_are_stable() {
+        assert_stable(|input| insertion_sort_by_key(input, &mut |element| element.0));
+        assert_stable(|input| binary_insertion_sort_by_key(input, &mut |element| element.0));
+    }
+
+    #[test]
+    fn test_heap_sort_by_key() {
+        let mut arr = [("b", 2), ("c", 3), ("a", 1)];
+        heap_sort_by_key(&mut arr, &mut |record| record.1);
+        assert_eq!(arr, [("a", 1), ("b", 2), ("c", 3)]);
+    }
+
+    #[test]
+    fn test_shell_gaps() {
+        assert_eq!(ShellGaps::Ciura.gaps(0), [1]);
+        assert_eq!(ShellGaps::Ciura.gaps(100), [57, 23, 10, 4, 1]);
+        assert_eq!(
+            ShellGaps::Ciura.gaps(5000),
+            [3937, 1750, 701, 301, 132, 57, 23, 10, 4, 1]
+        );
+        assert_eq!(ShellGaps::Sedgewick.gaps(300), [281, 77, 23, 8, 1]);
+        assert_eq!(ShellGaps::Tokuda.gaps(300), [233, 103, 46, 20, 9, 4, 1]);
+        assert_eq!(ShellGaps::Tokuda.gaps(1), [1]);
+        assert_eq!(ShellGaps::default(), ShellGaps::Ciura);
+    }
+
+    #[test]
+    fn test_apply_permutation() {
+        let mut arr = ['a', 'b', 'c', 'd', 'e'];
+        apply_permutation(&mut arr, &mut [3, 0, 4, 1, 2]);
+        assert_eq!(arr, ['d', 'a', 'e', 'b', 'c']);
+
+        let mut arr2 = [1, 2, 3];
+        apply_permutation(&mut arr2, &mut [0, 1, 2]);
+        assert_eq!(arr2, [1, 2, 3]);
+    }
+
     #[test]
     fn test_merge_sorted_into_buffer() {
         let mut arr = [1, 3, 5, 2, 4, 6, 0, 0, 0, 0, 0, 0];
-        let (lhs, rest) = arr.split_at_mut(3);
-        let (rhs, buffer) = rest.split_at_mut(3);
-        merge_sorted_into_buffer(lhs, rhs, buffer);
+        merge_sorted_into_buffer(&mut arr, 0..3, 3..6, 6, &mut i32::cmp, &mut ());
         assert_eq!(arr, [0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 5, 6]);
     }
 
     #[test]
     fn test_merge_sort_into_buffer() {
         let mut arr = [64, 25, 12, 22, 11, 0, 0, 0, 0, 0];
-        let (input, buffer) = arr.split_at_mut(5);
-        merge_sort_into_buffer(input, buffer);
+        merge_sort_into_buffer(&mut arr, 0..5, 5, &mut i32::cmp, &mut ());
         assert_eq!(arr, [11, 12, 22, 25, 64, 0, 0, 0, 0, 0]);
     }
 }
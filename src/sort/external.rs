@@ -0,0 +1,577 @@
+use super::{InPlaceInput, KWayMerge, quick_sort_by, stable_merge_sort_by};
+use std::cmp::Ordering;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+// How records are read from and written to byte streams, including the temporary runs
+pub trait RecordCodec {
+    type Record;
+
+    // The next record, or None at the end of the input
+    fn read_record<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<Self::Record>>;
+
+    fn write_record<W: Write>(&self, writer: &mut W, record: &Self::Record) -> io::Result<()>;
+
+    // Approximate memory taken by a record, counted against the memory budget
+    fn record_size(&self, record: &Self::Record) -> usize;
+}
+
+// Newline-delimited records, stored without the newline. Every record is
+// written back followed by a newline, even if the input did not end with one.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LinesCodec;
+
+impl RecordCodec for LinesCodec {
+    type Record = Vec<u8>;
+
+    fn read_record<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+        let mut line = Vec::new();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        Ok(Some(line))
+    }
+
+    fn write_record<W: Write>(&self, writer: &mut W, record: &Vec<u8>) -> io::Result<()> {
+        writer.write_all(record)?;
+        writer.write_all(b"\n")
+    }
+
+    fn record_size(&self, record: &Vec<u8>) -> usize {
+        std::mem::size_of::<Vec<u8>>() + record.len()
+    }
+}
+
+// Binary records of exactly `width` bytes each
+#[derive(Clone, Copy, Debug)]
+pub struct FixedWidthCodec {
+    pub width: usize,
+}
+
+impl RecordCodec for FixedWidthCodec {
+    type Record = Vec<u8>;
+
+    fn read_record<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+        let mut record = Vec::with_capacity(self.width);
+        let read = reader.take(self.width as u64).read_to_end(&mut record)?;
+        match read {
+            0 => Ok(None),
+            read if read == self.width => Ok(Some(record)),
+            read => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("truncated record of {read} bytes, expected {}", self.width),
+            )),
+        }
+    }
+
+    fn write_record<W: Write>(&self, writer: &mut W, record: &Vec<u8>) -> io::Result<()> {
+        writer.write_all(record)
+    }
+
+    fn record_size(&self, record: &Vec<u8>) -> usize {
+        std::mem::size_of::<Vec<u8>>() + record.len()
+    }
+}
+
+// Which in-memory sort is used for each chunk. With the stable merge sort the
+// whole external sort is stable, with quicksort it is not.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChunkSort {
+    #[default]
+    Merge,
+    Quick,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExternalSortOptions {
+    // Records are collected in memory until their total size reaches the budget,
+    // and every full chunk is spilled as a sorted run
+    pub memory_budget: usize,
+    pub chunk_sort: ChunkSort,
+    // Where the runs are spilled, they are removed once the sort is over
+    pub temp_dir: PathBuf,
+    // The most runs open at the same time in a merge. Each takes a file
+    // descriptor and a read buffer of RUN_BUFFER_SIZE bytes, which also count
+    // against the memory budget, and runs beyond that are merged in passes.
+    pub max_fan_in: usize,
+}
+
+pub const RUN_BUFFER_SIZE: usize = 8 << 10;
+
+impl Default for ExternalSortOptions {
+    fn default() -> Self {
+        ExternalSortOptions {
+            memory_budget: 64 << 20,
+            chunk_sort: ChunkSort::default(),
+            temp_dir: std::env::temp_dir(),
+            max_fan_in: 128,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExternalSortSummary {
+    pub records: usize,
+    // Number of sorted runs spilled to temporary files, 0 if the input fit in memory
+    pub runs: usize,
+    // Number of passes over the data merging runs, the last one into the output
+    pub merge_passes: usize,
+}
+
+// External merge sort for inputs which do not fit in memory.
+// The input is read in chunks bounded by the memory budget, every chunk is
+// sorted in memory and spilled to a temporary file as a sorted run, and the
+// runs are then merged with a heap into the output. When there are more runs
+// than the fan-in, groups of consecutive runs are first merged into longer
+// runs, pass after pass. An input which fits into a single chunk is written out
+// directly.
+// Complexity:
+// Time: O(n log n) comparisons, every record is written to disk and read back
+// once per pass, and there are ceil(log_f(r)) passes for r runs and fan-in f
+// Space: O(memory budget + fan-in * RUN_BUFFER_SIZE)
+pub fn external_sort<C, R, W>(
+    input: R,
+    output: W,
+    codec: &C,
+    options: &ExternalSortOptions,
+) -> io::Result<ExternalSortSummary>
+where
+    C: RecordCodec,
+    C::Record: Ord,
+    R: Read,
+    W: Write,
+{
+    external_sort_by(input, output, codec, options, &mut C::Record::cmp)
+}
+
+pub fn external_sort_by_key<C, R, W, K: Ord, F>(
+    input: R,
+    output: W,
+    codec: &C,
+    options: &ExternalSortOptions,
+    f: &mut F,
+) -> io::Result<ExternalSortSummary>
+where
+    C: RecordCodec,
+    R: Read,
+    W: Write,
+    F: FnMut(&C::Record) -> K,
+{
+    external_sort_by(input, output, codec, options, &mut |lhs, rhs| {
+        f(lhs).cmp(&f(rhs))
+    })
+}
+
+pub fn external_sort_by<C, R, W, F>(
+    input: R,
+    output: W,
+    codec: &C,
+    options: &ExternalSortOptions,
+    compare: &mut F,
+) -> io::Result<ExternalSortSummary>
+where
+    C: RecordCodec,
+    R: Read,
+    W: Write,
+    F: FnMut(&C::Record, &C::Record) -> Ordering,
+{
+    let mut input = BufReader::new(input);
+    let mut output = BufWriter::new(output);
+    let mut summary = ExternalSortSummary::default();
+    let mut runs: Vec<TempRun> = Vec::new();
+
+    loop {
+        let mut chunk = Vec::new();
+        let mut chunk_size = 0;
+        let mut finished = true;
+        while let Some(record) = codec.read_record(&mut input)? {
+            chunk_size += codec.record_size(&record);
+            chunk.push(record);
+            if chunk_size >= options.memory_budget {
+                finished = false;
+                break;
+            }
+        }
+        summary.records += chunk.len();
+        sort_chunk(&mut chunk, options.chunk_sort, compare);
+
+        if finished && runs.is_empty() {
+            for record in &chunk {
+                codec.write_record(&mut output, record)?;
+            }
+            output.flush()?;
+            return Ok(summary);
+        }
+        if !chunk.is_empty() {
+            runs.push(TempRun::spill(&options.temp_dir, codec, &chunk)?);
+        }
+        if finished {
+            break;
+        }
+    }
+
+    summary.runs = runs.len();
+    let fan_in = (options.memory_budget / RUN_BUFFER_SIZE).clamp(2, options.max_fan_in.max(2));
+    while runs.len() > fan_in {
+        let mut merged_runs = Vec::with_capacity(runs.len().div_ceil(fan_in));
+        for group in runs.chunks(fan_in) {
+            merged_runs.push(TempRun::create(&options.temp_dir, |writer| {
+                merge_runs(group, writer, codec, compare)
+            })?);
+        }
+        // Dropping the merged runs removes their files
+        runs = merged_runs;
+        summary.merge_passes += 1;
+    }
+
+    merge_runs(&runs, &mut output, codec, compare)?;
+    summary.merge_passes += 1;
+    output.flush()?;
+    Ok(summary)
+}
+
+fn sort_chunk<T, F>(chunk: InPlaceInput<T>, chunk_sort: ChunkSort, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    match chunk_sort {
+        ChunkSort::Merge => stable_merge_sort_by(chunk, compare),
+        ChunkSort::Quick => quick_sort_by(chunk, &mut rand::rng(), compare),
+    }
+}
+
+// Merges the runs into the output, ties are taken from earlier runs first
+fn merge_runs<C, W, F>(
+    runs: &[TempRun],
+    output: &mut W,
+    codec: &C,
+    compare: &mut F,
+) -> io::Result<()>
+where
+    C: RecordCodec,
+    W: Write,
+    F: FnMut(&C::Record, &C::Record) -> Ordering,
+{
+    let readers = runs
+        .iter()
+        .map(|run| {
+            Ok(RunReader {
+                reader: BufReader::with_capacity(RUN_BUFFER_SIZE, File::open(&run.path)?),
+                codec,
+                failed: false,
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    // Errors go first so they surface as soon as they happen
+    let merged = KWayMerge::new_by(readers, |lhs: &io::Result<C::Record>, rhs| {
+        match (lhs, rhs) {
+            (Ok(lhs), Ok(rhs)) => compare(lhs, rhs),
+            (Err(_), _) => Ordering::Less,
+            (_, Err(_)) => Ordering::Greater,
+        }
+    });
+    for record in merged {
+        codec.write_record(output, &record?)?;
+    }
+    Ok(())
+}
+
+// Reads the records of a run back, stopping after the first error
+struct RunReader<'a, C> {
+    reader: BufReader<File>,
+    codec: &'a C,
+    failed: bool,
+}
+
+impl<C: RecordCodec> Iterator for RunReader<'_, C> {
+    type Item = io::Result<C::Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let record = self.codec.read_record(&mut self.reader).transpose();
+        self.failed = matches!(record, Some(Err(_)));
+        record
+    }
+}
+
+// A sorted run spilled to a temporary file, which is removed on drop
+struct TempRun {
+    path: PathBuf,
+}
+
+impl TempRun {
+    fn spill<C: RecordCodec>(
+        temp_dir: &std::path::Path,
+        codec: &C,
+        records: &[C::Record],
+    ) -> io::Result<TempRun> {
+        TempRun::create(temp_dir, |writer| {
+            for record in records {
+                codec.write_record(writer, record)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn create<F>(temp_dir: &std::path::Path, write: F) -> io::Result<TempRun>
+    where
+        F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+    {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let id = NEXT_ID.fetch_add(1, AtomicOrdering::Relaxed);
+        let path = temp_dir.join(format!("cs-external-sort-{}-{id}.run", std::process::id()));
+
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let run = TempRun { path };
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        writer.flush()?;
+        Ok(run)
+    }
+}
+
+impl Drop for TempRun {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    // A scratch directory per test, so leftover runs can be detected
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "cs-external-sort-test-{name}-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn options(name: &str, memory_budget: usize, chunk_sort: ChunkSort) -> ExternalSortOptions {
+        ExternalSortOptions {
+            memory_budget,
+            chunk_sort,
+            temp_dir: temp_dir(name),
+            ..Default::default()
+        }
+    }
+
+    fn assert_cleaned_up(options: &ExternalSortOptions) {
+        assert_eq!(std::fs::read_dir(&options.temp_dir).unwrap().count(), 0);
+        std::fs::remove_dir(&options.temp_dir).unwrap();
+    }
+
+    // Lines are never empty: an empty last line would be joined into a trailing
+    // newline, which reads back as no record at all
+    fn random_lines(count: usize) -> Vec<String> {
+        let mut rng = rand::rng();
+        (0..count)
+            .map(|_| {
+                let len = rng.random_range(1..20);
+                (0..len).map(|_| rng.random_range('a'..='f')).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lines_spilled_to_runs() {
+        let lines = random_lines(5000);
+        let input = lines.join("\n");
+        let mut expected = lines.clone();
+        expected.sort();
+
+        for (name, chunk_sort) in [("merge", ChunkSort::Merge), ("quick", ChunkSort::Quick)] {
+            let options = options(name, 4096, chunk_sort);
+            let mut output = Vec::new();
+            let summary =
+                external_sort(input.as_bytes(), &mut output, &LinesCodec, &options).unwrap();
+            assert_eq!(summary.records, 5000);
+            assert!(summary.runs > 10, "{} runs", summary.runs);
+
+            let output = String::from_utf8(output).unwrap();
+            assert_eq!(output.lines().collect::<Vec<&str>>(), expected);
+            assert!(output.ends_with('\n'));
+            assert_cleaned_up(&options);
+        }
+    }
+
+    #[test]
+    fn test_fits_in_memory() {
+        let options = options("in-memory", 1 << 20, ChunkSort::Merge);
+        let mut output = Vec::new();
+        let summary = external_sort(
+            &b"pear\napple\nfig\n"[..],
+            &mut output,
+            &LinesCodec,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            summary,
+            ExternalSortSummary {
+                records: 3,
+                runs: 0,
+                merge_passes: 0,
+            }
+        );
+        assert_eq!(output, b"apple\nfig\npear\n");
+
+        let mut output = Vec::new();
+        let summary = external_sort(&b""[..], &mut output, &LinesCodec, &options).unwrap();
+        assert_eq!(summary, ExternalSortSummary::default());
+        assert!(output.is_empty());
+        assert_cleaned_up(&options);
+    }
+
+    #[test]
+    fn test_fixed_width_records() {
+        let mut rng = rand::rng();
+        let values: Vec<u32> = (0..3000).map(|_| rng.random()).collect();
+        let input: Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect();
+        let mut expected = values.clone();
+        expected.sort();
+
+        let options = options("fixed-width", 1000, ChunkSort::Merge);
+        let mut output = Vec::new();
+        let codec = FixedWidthCodec { width: 4 };
+        let summary = external_sort(&input[..], &mut output, &codec, &options).unwrap();
+        assert_eq!(summary.records, 3000);
+        let sorted: Vec<u32> = output
+            .chunks(4)
+            .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+            .collect();
+        assert_eq!(sorted, expected);
+
+        let error = external_sort(&input[..7], &mut Vec::new(), &codec, &options).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        assert_cleaned_up(&options);
+    }
+
+    #[test]
+    fn test_custom_order() {
+        let numbers: Vec<String> = (0..2000).map(|i| ((i * 7919) % 2003).to_string()).collect();
+        let input = numbers.join("\n");
+        let mut expected: Vec<u32> = numbers
+            .iter()
+            .map(|number| number.parse().unwrap())
+            .collect();
+        expected.sort_by(|lhs, rhs| rhs.cmp(lhs));
+
+        let options = options("custom-order", 512, ChunkSort::Quick);
+        let mut output = Vec::new();
+        let mut numeric = |line: &Vec<u8>| {
+            std::cmp::Reverse(std::str::from_utf8(line).unwrap().parse::<u32>().unwrap())
+        };
+        let summary = external_sort_by_key(
+            input.as_bytes(),
+            &mut output,
+            &LinesCodec,
+            &options,
+            &mut numeric,
+        )
+        .unwrap();
+        assert!(summary.runs > 1);
+        let sorted: Vec<u32> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        assert_eq!(sorted, expected);
+        assert_cleaned_up(&options);
+    }
+
+    #[test]
+    fn test_equal_records_keep_run_order() {
+        // Records compare by their first byte only, the merge takes ties from earlier runs first
+        let input = "b1\na1\nb2\na2\nb3\na3\n";
+        let options = options("ties", 1, ChunkSort::Merge);
+        let mut output = Vec::new();
+        external_sort_by(
+            input.as_bytes(),
+            &mut output,
+            &LinesCodec,
+            &options,
+            &mut |lhs, rhs| lhs[0].cmp(&rhs[0]),
+        )
+        .unwrap();
+        assert_eq!(output, b"a1\na2\na3\nb1\nb2\nb3\n");
+        assert_cleaned_up(&options);
+    }
+
+    #[test]
+    fn test_merge_passes() {
+        let lines = random_lines(50_000);
+        let input = lines.join("\n");
+        let mut expected = lines.clone();
+        expected.sort();
+
+        // The budget has room for 8 read buffers, which caps the fan-in
+        let memory_budget = 8 * RUN_BUFFER_SIZE;
+        for (max_fan_in, fan_in) in [(0, 2), (2, 2), (3, 3), (1000, 8)] {
+            let options = ExternalSortOptions {
+                max_fan_in,
+                ..options("passes", memory_budget, ChunkSort::Quick)
+            };
+            let mut output = Vec::new();
+            let summary =
+                external_sort(input.as_bytes(), &mut output, &LinesCodec, &options).unwrap();
+            assert!(summary.runs > 20, "{} runs", summary.runs);
+
+            let mut runs = summary.runs;
+            let mut passes = 1;
+            while runs > fan_in {
+                runs = runs.div_ceil(fan_in);
+                passes += 1;
+            }
+            assert_eq!(summary.merge_passes, passes, "{max_fan_in} {summary:?}");
+
+            let output = String::from_utf8(output).unwrap();
+            assert_eq!(output.lines().collect::<Vec<&str>>(), expected);
+            assert_cleaned_up(&options);
+        }
+    }
+
+    #[test]
+    fn test_stable_with_merge_chunks() {
+        let lines = random_lines(3000);
+        let input = lines.join("\n");
+        let mut expected = lines.clone();
+        expected.sort_by_key(|line| line.as_bytes()[0]);
+
+        let options = ExternalSortOptions {
+            max_fan_in: 3,
+            ..options("stable", 1024, ChunkSort::Merge)
+        };
+        let mut output = Vec::new();
+        let summary = external_sort_by_key(
+            input.as_bytes(),
+            &mut output,
+            &LinesCodec,
+            &options,
+            &mut |line| line[0],
+        )
+        .unwrap();
+        assert!(summary.merge_passes > 1, "{summary:?}");
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().collect::<Vec<&str>>(), expected);
+        assert_cleaned_up(&options);
+    }
+}
@@ -0,0 +1,265 @@
+use super::sift_down_by;
+use std::cmp::Ordering;
+
+// The comparator used when the items are merged by their natural order
+type NaturalOrder<T> = fn(&T, &T) -> Ordering;
+
+// Merges any number of sorted iterators into one sorted stream.
+// The current head of every source is kept in a binary min-heap, so each item
+// costs O(log k) comparisons for k sources. Equal items are yielded in the
+// order of their sources, and from each source in their original order.
+pub struct KWayMerge<I: Iterator, F> {
+    sources: Vec<I>,
+    // Heads of the sources which are not exhausted, with the index of their source
+    heap: Vec<(I::Item, usize)>,
+    compare: F,
+}
+
+impl<I: Iterator> KWayMerge<I, NaturalOrder<I::Item>>
+where
+    I::Item: Ord,
+{
+    pub fn new<S>(sources: S) -> Self
+    where
+        S: IntoIterator,
+        S::Item: IntoIterator<IntoIter = I>,
+    {
+        KWayMerge::new_by(sources, I::Item::cmp)
+    }
+}
+
+impl<I: Iterator, F> KWayMerge<I, F>
+where
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    pub fn new_by<S>(sources: S, compare: F) -> Self
+    where
+        S: IntoIterator,
+        S::Item: IntoIterator<IntoIter = I>,
+    {
+        let mut sources: Vec<I> = sources.into_iter().map(IntoIterator::into_iter).collect();
+        let heap = sources
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, source)| source.next().map(|item| (item, index)))
+            .collect();
+        let mut merge = KWayMerge {
+            sources,
+            heap,
+            compare,
+        };
+        for node in (0..merge.heap.len() / 2).rev() {
+            merge.sift_down(node);
+        }
+        merge
+    }
+
+    fn sift_down(&mut self, node: usize) {
+        let compare = &mut self.compare;
+        // Reversed for a min-heap, ties go to the lower source index
+        sift_down_by(&mut self.heap, node, &mut |lhs, rhs| {
+            compare(&rhs.0, &lhs.0).then(rhs.1.cmp(&lhs.1))
+        });
+    }
+}
+
+impl<I: Iterator, F> Iterator for KWayMerge<I, F>
+where
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let source = self.heap.first()?.1;
+        let item = match self.sources[source].next() {
+            Some(next) => std::mem::replace(&mut self.heap[0].0, next),
+            None => self.heap.swap_remove(0).0,
+        };
+        self.sift_down(0);
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.sources.iter().fold(
+            (self.heap.len(), Some(self.heap.len())),
+            |(low, high), source| {
+                let (source_low, source_high) = source.size_hint();
+                (
+                    low.saturating_add(source_low),
+                    high.zip(source_high)
+                        .and_then(|(high, source_high)| high.checked_add(source_high)),
+                )
+            },
+        )
+    }
+}
+
+// Merges two sorted iterators, taking from the left one on ties
+pub struct Merge<L: Iterator, R: Iterator<Item = L::Item>, F> {
+    lhs: L,
+    rhs: R,
+    lhs_head: Option<L::Item>,
+    rhs_head: Option<L::Item>,
+    compare: F,
+}
+
+pub fn merge<L, R>(lhs: L, rhs: R) -> Merge<L::IntoIter, R::IntoIter, NaturalOrder<L::Item>>
+where
+    L: IntoIterator,
+    R: IntoIterator<Item = L::Item>,
+    L::Item: Ord,
+{
+    merge_by(lhs, rhs, L::Item::cmp)
+}
+
+pub fn merge_by<L, R, F>(lhs: L, rhs: R, compare: F) -> Merge<L::IntoIter, R::IntoIter, F>
+where
+    L: IntoIterator,
+    R: IntoIterator<Item = L::Item>,
+    F: FnMut(&L::Item, &L::Item) -> Ordering,
+{
+    let mut lhs = lhs.into_iter();
+    let mut rhs = rhs.into_iter();
+    Merge {
+        lhs_head: lhs.next(),
+        rhs_head: rhs.next(),
+        lhs,
+        rhs,
+        compare,
+    }
+}
+
+impl<L: Iterator, R: Iterator<Item = L::Item>, F> Iterator for Merge<L, R, F>
+where
+    F: FnMut(&L::Item, &L::Item) -> Ordering,
+{
+    type Item = L::Item;
+
+    fn next(&mut self) -> Option<L::Item> {
+        let take_from_lhs = match (&self.lhs_head, &self.rhs_head) {
+            (None, None) => return None,
+            (Some(lhs), Some(rhs)) => (self.compare)(rhs, lhs) != Ordering::Less,
+            (lhs, _) => lhs.is_some(),
+        };
+        if take_from_lhs {
+            std::mem::replace(&mut self.lhs_head, self.lhs.next())
+        } else {
+            std::mem::replace(&mut self.rhs_head, self.rhs.next())
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let heads = self.lhs_head.is_some() as usize + self.rhs_head.is_some() as usize;
+        let (lhs_low, lhs_high) = self.lhs.size_hint();
+        let (rhs_low, rhs_high) = self.rhs.size_hint();
+        let high = lhs_high
+            .zip(rhs_high)
+            .and_then(|(lhs, rhs)| lhs.checked_add(rhs)?.checked_add(heads));
+        (lhs_low.saturating_add(rhs_low).saturating_add(heads), high)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_k_way_merge_against_sort() {
+        let mut rng = rand::rng();
+        for sources_count in [0, 1, 2, 3, 10, 50] {
+            let sources: Vec<Vec<i32>> = (0..sources_count)
+                .map(|_| {
+                    let len = rng.random_range(0..100);
+                    let mut source: Vec<i32> = (0..len).map(|_| rng.random_range(0..50)).collect();
+                    source.sort();
+                    source
+                })
+                .collect();
+            let mut expected: Vec<i32> = sources.concat();
+            expected.sort();
+
+            let merge = KWayMerge::new(sources.iter().map(|source| source.iter().copied()));
+            assert_eq!(merge.size_hint(), (expected.len(), Some(expected.len())));
+            assert_eq!(merge.collect::<Vec<i32>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_k_way_merge_ties_by_source() {
+        let sources = vec![
+            vec![(1, 'a'), (3, 'a')],
+            vec![(1, 'b'), (2, 'b'), (3, 'b')],
+            vec![],
+            vec![(1, 'd'), (3, 'd'), (3, 'e')],
+        ];
+        let merged: Vec<(i32, char)> =
+            KWayMerge::new_by(sources, |lhs: &(i32, char), rhs: &(i32, char)| {
+                lhs.0.cmp(&rhs.0)
+            })
+            .collect();
+        assert_eq!(
+            merged,
+            [
+                (1, 'a'),
+                (1, 'b'),
+                (1, 'd'),
+                (2, 'b'),
+                (3, 'a'),
+                (3, 'b'),
+                (3, 'd'),
+                (3, 'e')
+            ]
+        );
+    }
+
+    #[test]
+    fn test_k_way_merge_descending_streams() {
+        let shards = [
+            vec![9.5, 3.0, -1.0],
+            vec![10.0, 2.0],
+            vec![f64::INFINITY, 0.0],
+        ];
+        let merged: Vec<f64> =
+            KWayMerge::new_by(shards, |lhs: &f64, rhs: &f64| rhs.total_cmp(lhs)).collect();
+        assert_eq!(merged, [f64::INFINITY, 10.0, 9.5, 3.0, 2.0, 0.0, -1.0]);
+    }
+
+    #[test]
+    fn test_merge() {
+        assert_eq!(
+            merge([1, 4, 6], [2, 3, 7, 8]).collect::<Vec<i32>>(),
+            [1, 2, 3, 4, 6, 7, 8]
+        );
+        assert_eq!(
+            merge(Vec::<i32>::new(), [1, 2]).collect::<Vec<i32>>(),
+            [1, 2]
+        );
+        assert_eq!(merge([1, 2], []).collect::<Vec<i32>>(), [1, 2]);
+
+        let merged: Vec<(i32, char)> =
+            merge_by([(1, 'l'), (2, 'l')], [(1, 'r'), (2, 'r')], |lhs, rhs| {
+                lhs.0.cmp(&rhs.0)
+            })
+            .collect();
+        assert_eq!(merged, [(1, 'l'), (1, 'r'), (2, 'l'), (2, 'r')]);
+
+        let words = merge(
+            ["apple", "fig"].map(String::from),
+            ["banana", "kiwi"].map(String::from),
+        );
+        assert_eq!(words.size_hint(), (4, Some(4)));
+        assert_eq!(
+            words.collect::<Vec<String>>(),
+            ["apple", "banana", "fig", "kiwi"]
+        );
+    }
+
+    #[test]
+    fn test_lazy_sources() {
+        // Infinite sources are fine as long as only a prefix is consumed
+        let multiples = (1..4).map(|step| (1..).map(move |i| i * step));
+        let merged: Vec<u64> = KWayMerge::new(multiples).take(8).collect();
+        assert_eq!(merged, [1, 2, 2, 3, 3, 4, 4, 5]);
+    }
+}
@@ -0,0 +1,295 @@
+use std::cmp::Ordering;
+
+// Instrumentation hooks for the sorts that have an `_observed` variant.
+// The sort reports every comparison and every swap it makes, by the indices
+// of the elements in the whole input, before performing it.
+// All the hooks do nothing by default, and `()` is the observer which
+// ignores everything, so the plain sorts pay nothing for the instrumentation.
+pub trait SortObserver {
+    fn on_compare(&mut self, _lhs: usize, _rhs: usize) {}
+
+    fn on_swap(&mut self, _lhs: usize, _rhs: usize) {}
+}
+
+impl SortObserver for () {}
+
+impl<O: SortObserver + ?Sized> SortObserver for &mut O {
+    fn on_compare(&mut self, lhs: usize, rhs: usize) {
+        (**self).on_compare(lhs, rhs);
+    }
+
+    fn on_swap(&mut self, lhs: usize, rhs: usize) {
+        (**self).on_swap(lhs, rhs);
+    }
+}
+
+// Reports every operation to both observers, e.g. to count and trace at once.
+impl<A: SortObserver, B: SortObserver> SortObserver for (A, B) {
+    fn on_compare(&mut self, lhs: usize, rhs: usize) {
+        self.0.on_compare(lhs, rhs);
+        self.1.on_compare(lhs, rhs);
+    }
+
+    fn on_swap(&mut self, lhs: usize, rhs: usize) {
+        self.0.on_swap(lhs, rhs);
+        self.1.on_swap(lhs, rhs);
+    }
+}
+
+// Operation counts of a sort. The observed sorts only ever move elements by
+// swapping them, merge sort's buffer included. `moves` counts the element
+// moves of those swaps as if each went through a temporary: three per swap of
+// two different positions, none for swapping an element with itself. It can be
+// compared with sorts which shift elements one move at a time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Counters {
+    pub comparisons: usize,
+    pub swaps: usize,
+    pub moves: usize,
+}
+
+impl SortObserver for Counters {
+    fn on_compare(&mut self, _lhs: usize, _rhs: usize) {
+        self.comparisons += 1;
+    }
+
+    fn on_swap(&mut self, lhs: usize, rhs: usize) {
+        self.swaps += 1;
+        if lhs != rhs {
+            self.moves += 3;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Compare(usize, usize),
+    Swap(usize, usize),
+}
+
+// The full list of operations of a sort, in the order they were made.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub operations: Vec<Operation>,
+}
+
+impl SortObserver for Trace {
+    fn on_compare(&mut self, lhs: usize, rhs: usize) {
+        self.operations.push(Operation::Compare(lhs, rhs));
+    }
+
+    fn on_swap(&mut self, lhs: usize, rhs: usize) {
+        self.operations.push(Operation::Swap(lhs, rhs));
+    }
+}
+
+impl Trace {
+    pub fn counters(&self) -> Counters {
+        let mut counters = Counters::default();
+        for operation in &self.operations {
+            match *operation {
+                Operation::Compare(lhs, rhs) => counters.on_compare(lhs, rhs),
+                Operation::Swap(lhs, rhs) => counters.on_swap(lhs, rhs),
+            }
+        }
+        counters
+    }
+
+    // Replays the first `steps` operations on a copy of the input the trace was
+    // recorded on, leaving it in the state the sort had reached at that point.
+    // Comparisons don't change anything, so replaying the whole trace sorts it.
+    pub fn replay<T>(&self, input: &mut [T], steps: usize) {
+        for operation in self.operations.iter().take(steps) {
+            if let Operation::Swap(lhs, rhs) = *operation {
+                input.swap(lhs, rhs);
+            }
+        }
+    }
+}
+
+// Compares input[lhs] with input[rhs], reporting the indices shifted by `offset`,
+// the position of `input` inside the slice being sorted.
+pub(super) fn compare_at<T, F, O>(
+    input: &[T],
+    offset: usize,
+    lhs: usize,
+    rhs: usize,
+    compare: &mut F,
+    observer: &mut O,
+) -> Ordering
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    observer.on_compare(offset + lhs, offset + rhs);
+    compare(&input[lhs], &input[rhs])
+}
+
+pub(super) fn swap_at<T, O: SortObserver>(
+    input: &mut [T],
+    offset: usize,
+    lhs: usize,
+    rhs: usize,
+    observer: &mut O,
+) {
+    observer.on_swap(offset + lhs, offset + rhs);
+    input.swap(lhs, rhs);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::{
+        merge_sort_by_observed, merge_sort_observed, quick_sort_by_observed, quick_sort_observed,
+        selection_sort_by_observed, selection_sort_observed,
+    };
+    use rand::Rng;
+    use rand::seq::SliceRandom;
+
+    fn random_input(len: usize) -> Vec<i32> {
+        let mut rng = rand::rng();
+        (0..len).map(|_| rng.random_range(-100..100)).collect()
+    }
+
+    fn n_log_n(len: usize) -> usize {
+        len * len.checked_ilog2().unwrap_or(0) as usize
+    }
+
+    #[test]
+    fn test_selection_sort_bounds() {
+        for len in [0, 1, 2, 10, 100, 500] {
+            let mut input = random_input(len);
+            let mut counters = Counters::default();
+            selection_sort_observed(&mut input, &mut counters);
+
+            assert!(input.is_sorted());
+            assert_eq!(counters.comparisons, len * len.saturating_sub(1) / 2);
+            assert!(counters.swaps < len.max(1));
+            assert!(counters.moves <= 3 * counters.swaps);
+        }
+    }
+
+    #[test]
+    fn test_quick_sort_bounds() {
+        let mut rng = rand::rng();
+        for len in [1000, 10_000] {
+            let mut input = random_input(len);
+            let mut counters = Counters::default();
+            quick_sort_observed(&mut input, &mut rng, &mut counters);
+
+            assert!(input.is_sorted());
+            assert!(counters.comparisons <= 3 * n_log_n(len));
+            assert!(counters.swaps <= 3 * n_log_n(len));
+        }
+
+        // Only two distinct values: three-way partitioning makes it linear
+        let mut input: Vec<i32> = (0..10_000).map(|i| i % 2).collect();
+        let mut counters = Counters::default();
+        quick_sort_observed(&mut input, &mut rng, &mut counters);
+        assert!(input.is_sorted());
+        assert!(counters.comparisons <= 3 * input.len());
+    }
+
+    #[test]
+    fn test_merge_sort_bounds() {
+        for len in [1, 2, 3, 1000, 10_000] {
+            let mut input = random_input(len);
+            let mut counters = Counters::default();
+            merge_sort_observed(&mut input, &mut counters);
+
+            assert!(input.is_sorted());
+            assert!(counters.comparisons <= 2 * n_log_n(len) + len);
+            // Every element goes to the buffer and back on each level
+            assert!(counters.swaps <= 4 * n_log_n(len) + 2 * len);
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut rng = rand::rng();
+        let mut original: Vec<i32> = (0..200).map(|i| i % 50).collect();
+        original.shuffle(&mut rng);
+
+        type ObservedSort = dyn Fn(&mut [i32], &mut dyn FnMut(&i32, &i32) -> Ordering, &mut Trace);
+        let sorts: [&ObservedSort; 3] = [
+            &|input, mut compare, trace| selection_sort_by_observed(input, &mut compare, trace),
+            &|input, mut compare, trace| {
+                quick_sort_by_observed(input, &mut rand::rng(), &mut compare, trace)
+            },
+            &|input, mut compare, trace| merge_sort_by_observed(input, &mut compare, trace),
+        ];
+        for sort in sorts {
+            let mut input = original.clone();
+            let mut trace = Trace::default();
+            let mut compared = Vec::new();
+            sort(
+                &mut input,
+                &mut |lhs, rhs| {
+                    compared.push((*lhs, *rhs));
+                    lhs.cmp(rhs)
+                },
+                &mut trace,
+            );
+            assert!(input.is_sorted());
+
+            let mut replayed = original.clone();
+            trace.replay(&mut replayed, trace.operations.len());
+            assert_eq!(replayed, input);
+
+            // Replayed step by step, every recorded comparison points at the
+            // elements the sort actually compared
+            let mut compared = compared.into_iter();
+            let mut replayed = original.clone();
+            for (step, operation) in trace.operations.iter().enumerate() {
+                if step % 64 == 0 {
+                    let mut prefix = original.clone();
+                    trace.replay(&mut prefix, step);
+                    assert_eq!(prefix, replayed);
+                }
+                match *operation {
+                    Operation::Compare(lhs, rhs) => {
+                        assert_eq!(compared.next(), Some((replayed[lhs], replayed[rhs])))
+                    }
+                    Operation::Swap(lhs, rhs) => replayed.swap(lhs, rhs),
+                }
+            }
+            assert_eq!(compared.next(), None);
+        }
+    }
+
+    #[test]
+    fn test_moves() {
+        let mut counters = Counters::default();
+        counters.on_swap(3, 3);
+        assert_eq!(counters.moves, 0);
+        counters.on_swap(1, 4);
+        assert_eq!(
+            counters,
+            Counters {
+                comparisons: 0,
+                swaps: 2,
+                moves: 3
+            }
+        );
+
+        let mut input = random_input(1000);
+        let mut trace = Trace::default();
+        quick_sort_observed(&mut input, &mut rand::rng(), &mut trace);
+        let distinct_swaps = trace
+            .operations
+            .iter()
+            .filter(|operation| matches!(operation, Operation::Swap(lhs, rhs) if lhs != rhs))
+            .count();
+        assert_eq!(trace.counters().moves, 3 * distinct_swaps);
+    }
+
+    #[test]
+    fn test_trace_counters() {
+        let mut input = random_input(300);
+        let mut trace = Trace::default();
+        let mut counters = Counters::default();
+        merge_sort_by_observed(&mut input, &mut i32::cmp, &mut (&mut trace, &mut counters));
+
+        assert_eq!(trace.counters(), counters);
+    }
+}
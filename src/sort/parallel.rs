@@ -0,0 +1,268 @@
+use super::tim_sort::{merge_runs_by, tim_sort_by};
+use super::{
+    InPlaceInput, choose_pivot, intro_sort_by, split_merge_by_rotation, three_way_partition,
+};
+use std::cmp::Ordering;
+
+// How the parallel sorts split their work
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParallelOptions {
+    // The most threads sorting at the same time, the calling one included
+    pub threads: usize,
+    // Slices not longer than this are sorted sequentially
+    pub sequential_cutoff: usize,
+}
+
+impl Default for ParallelOptions {
+    fn default() -> Self {
+        ParallelOptions {
+            threads: std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            sequential_cutoff: 1 << 13,
+        }
+    }
+}
+
+// Quicksort which sorts the two sides of each partition on separate scoped
+// threads, halving the thread budget at every level. Partitioning is three-way
+// around a median-of-three or ninther pivot, and slices which are short or out
+// of threads are finished by `intro_sort`, so the worst case stays O(n log n).
+// Like the sequential quick sorts it is not stable.
+pub fn par_quick_sort<T: Ord + Send>(input: InPlaceInput<T>, options: &ParallelOptions) {
+    par_quick_sort_by(input, options, &T::cmp);
+}
+
+pub fn par_quick_sort_by_key<T: Send, K: Ord, F>(
+    input: InPlaceInput<T>,
+    options: &ParallelOptions,
+    f: &F,
+) where
+    F: Fn(&T) -> K + Sync,
+{
+    par_quick_sort_by(input, options, &|lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn par_quick_sort_by<T: Send, F>(input: InPlaceInput<T>, options: &ParallelOptions, compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    quick_sort_on_threads(input, options.threads, options.sequential_cutoff, compare);
+}
+
+fn quick_sort_on_threads<T: Send, F>(
+    input: InPlaceInput<T>,
+    threads: usize,
+    sequential_cutoff: usize,
+    compare: &F,
+) where
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let mut compare_mut = |lhs: &T, rhs: &T| compare(lhs, rhs);
+    if threads <= 1 || input.len() <= sequential_cutoff.max(1) {
+        intro_sort_by(input, &mut compare_mut);
+        return;
+    }
+
+    let pivot_index = choose_pivot(input, &mut compare_mut);
+    let (less_end, greater_start) = three_way_partition(input, pivot_index, &mut compare_mut);
+    let (less, rest) = input.split_at_mut(less_end);
+    let greater = &mut rest[greater_start - less_end..];
+
+    let less_threads = threads / 2;
+    std::thread::scope(|scope| {
+        scope.spawn(|| quick_sort_on_threads(less, less_threads, sequential_cutoff, compare));
+        quick_sort_on_threads(greater, threads - less_threads, sequential_cutoff, compare);
+    });
+}
+
+// Merge sort which sorts the two halves on separate scoped threads, halving
+// the thread budget at every level, and merges them on the way back.
+// The halving goes on sequentially once the threads run out, so that only
+// slices up to the sequential cutoff are sorted by `tim_sort` and merged by its
+// galloping merge. Longer merges are cut into independent ones by the rotation
+// step of `stable_merge_sort`, which also hands the pieces to separate threads.
+// The merges keep the order of equal elements, so the sort is stable.
+// Complexity:
+// Time: O(n log^2 n) in the worst case, for the rotations of the long merges
+// Space: O(threads * sequential_cutoff), as only short merges need an order
+// buffer
+pub fn par_merge_sort<T: Ord + Send>(input: InPlaceInput<T>, options: &ParallelOptions) {
+    par_merge_sort_by(input, options, &T::cmp);
+}
+
+pub fn par_merge_sort_by_key<T: Send, K: Ord, F>(
+    input: InPlaceInput<T>,
+    options: &ParallelOptions,
+    f: &F,
+) where
+    F: Fn(&T) -> K + Sync,
+{
+    par_merge_sort_by(input, options, &|lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn par_merge_sort_by<T: Send, F>(input: InPlaceInput<T>, options: &ParallelOptions, compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    merge_sort_on_threads(input, options.threads, options.sequential_cutoff, compare);
+}
+
+fn merge_sort_on_threads<T: Send, F>(
+    input: InPlaceInput<T>,
+    threads: usize,
+    sequential_cutoff: usize,
+    compare: &F,
+) where
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if input.len() <= sequential_cutoff.max(1) {
+        tim_sort_by(input, &mut |lhs: &T, rhs: &T| compare(lhs, rhs));
+        return;
+    }
+
+    let mid = input.len() / 2;
+    let (lhs, rhs) = input.split_at_mut(mid);
+    if threads <= 1 {
+        merge_sort_on_threads(lhs, 1, sequential_cutoff, compare);
+        merge_sort_on_threads(rhs, 1, sequential_cutoff, compare);
+    } else {
+        let lhs_threads = threads / 2;
+        std::thread::scope(|scope| {
+            scope.spawn(|| merge_sort_on_threads(lhs, lhs_threads, sequential_cutoff, compare));
+            merge_sort_on_threads(rhs, threads - lhs_threads, sequential_cutoff, compare);
+        });
+    }
+    merge_on_threads(input, mid, threads, sequential_cutoff, compare);
+}
+
+// Stable merge of the sorted input[..mid] and input[mid..]
+fn merge_on_threads<T: Send, F>(
+    input: InPlaceInput<T>,
+    mid: usize,
+    threads: usize,
+    sequential_cutoff: usize,
+    compare: &F,
+) where
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let mut compare_mut = |lhs: &T, rhs: &T| compare(lhs, rhs);
+    let len = input.len();
+    if mid == 0 || mid == len || compare(&input[mid - 1], &input[mid]) != Ordering::Greater {
+        return;
+    }
+    if len <= sequential_cutoff.max(2) {
+        merge_runs_by(input, mid, &mut compare_mut);
+        return;
+    }
+
+    let (split, first_mid, second_mid) = split_merge_by_rotation(input, mid, &mut compare_mut);
+    let (first, second) = input.split_at_mut(split);
+    if threads <= 1 {
+        merge_on_threads(first, first_mid, 1, sequential_cutoff, compare);
+        merge_on_threads(second, second_mid, 1, sequential_cutoff, compare);
+    } else {
+        let first_threads = threads / 2;
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                merge_on_threads(first, first_mid, first_threads, sequential_cutoff, compare)
+            });
+            merge_on_threads(
+                second,
+                second_mid,
+                threads - first_threads,
+                sequential_cutoff,
+                compare,
+            );
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::tests::{assert_stable, check_sort_battery};
+    use crate::sort::{merge_sort, quick_sort, stable_merge_sort_by_key};
+    use rand::Rng;
+
+    const OPTIONS: ParallelOptions = ParallelOptions {
+        threads: 4,
+        sequential_cutoff: 64,
+    };
+
+    #[test]
+    fn test_battery() {
+        check_sort_battery(|input| par_quick_sort(input, &OPTIONS));
+        check_sort_battery(|input| par_merge_sort(input, &OPTIONS));
+        check_sort_battery(|input| par_quick_sort(input, &ParallelOptions::default()));
+        check_sort_battery(|input| par_merge_sort(input, &ParallelOptions::default()));
+    }
+
+    #[test]
+    fn test_same_output_as_sequential() {
+        let mut rng = rand::rng();
+        for distinct in [3, 1000, u32::MAX] {
+            let input: Vec<u32> = (0..100_000)
+                .map(|_| rng.random_range(0..distinct))
+                .collect();
+
+            let mut expected = input.clone();
+            quick_sort(&mut expected, &mut rng);
+            let mut actual = input.clone();
+            par_quick_sort(&mut actual, &OPTIONS);
+            assert_eq!(actual, expected);
+
+            let mut expected = input.clone();
+            merge_sort(&mut expected);
+            let mut actual = input.clone();
+            par_merge_sort(&mut actual, &OPTIONS);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_thread_budgets() {
+        let mut rng = rand::rng();
+        let input: Vec<i64> = (0..10_000).map(|_| rng.random()).collect();
+        let mut expected = input.clone();
+        expected.sort();
+        for threads in [0, 1, 2, 3, 7, 16] {
+            for sequential_cutoff in [0, 1, 100, 1_000_000] {
+                let options = ParallelOptions {
+                    threads,
+                    sequential_cutoff,
+                };
+                let mut actual = input.clone();
+                par_quick_sort(&mut actual, &options);
+                assert_eq!(actual, expected);
+                let mut actual = input.clone();
+                par_merge_sort(&mut actual, &options);
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_par_merge_sort_is_stable() {
+        assert_stable(|input| par_merge_sort_by_key(input, &OPTIONS, &|element| element.0));
+
+        let mut rng = rand::rng();
+        let input: Vec<(u8, usize)> = (0..50_000).map(|i| (rng.random_range(0..10), i)).collect();
+        let mut expected = input.clone();
+        stable_merge_sort_by_key(&mut expected, &mut |element| element.0);
+        let mut actual = input;
+        par_merge_sort_by(&mut actual, &OPTIONS, &|lhs, rhs| lhs.0.cmp(&rhs.0));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_par_quick_sort_by_key() {
+        let mut words: Vec<String> = (0..5000)
+            .map(|i| format!("{:x}", i * 7919 % 5003))
+            .collect();
+        let mut expected = words.clone();
+        expected.sort_by_key(|word| std::cmp::Reverse(word.clone()));
+        par_quick_sort_by_key(&mut words, &OPTIONS, &|word| {
+            std::cmp::Reverse(word.clone())
+        });
+        assert_eq!(words, expected);
+    }
+}
@@ -0,0 +1,502 @@
+use super::{InPlaceInput, apply_permutation, insertion_sort_by, intro_sort_by};
+
+const MSD_INSERTION_SORT_THRESHOLD: usize = 16;
+
+// Keys which can be sorted byte by byte without comparisons.
+// `radix_byte(0)` is the least significant byte of an unsigned representation
+// of the key whose order matches the order of the keys themselves.
+pub trait RadixKey: Copy {
+    const BYTES: usize;
+
+    fn radix_byte(&self, index: usize) -> u8;
+}
+
+macro_rules! impl_radix_key_for_unsigned {
+    ($($unsigned:ty),*) => {$(
+        impl RadixKey for $unsigned {
+            const BYTES: usize = std::mem::size_of::<$unsigned>();
+
+            fn radix_byte(&self, index: usize) -> u8 {
+                (*self >> (8 * index)) as u8
+            }
+        }
+    )*};
+}
+
+// Flipping the sign bit maps the two's complement order onto the unsigned one
+macro_rules! impl_radix_key_for_signed {
+    ($($signed:ty => $unsigned:ty),*) => {$(
+        impl RadixKey for $signed {
+            const BYTES: usize = std::mem::size_of::<$signed>();
+
+            fn radix_byte(&self, index: usize) -> u8 {
+                let flipped = (*self as $unsigned) ^ (1 << (<$unsigned>::BITS - 1));
+                (flipped >> (8 * index)) as u8
+            }
+        }
+    )*};
+}
+
+// Negative floats get all their bits inverted and positive ones only the sign
+// bit, which orders them the same way as `total_cmp`
+macro_rules! impl_radix_key_for_float {
+    ($($float:ty => $unsigned:ty),*) => {$(
+        impl RadixKey for $float {
+            const BYTES: usize = std::mem::size_of::<$float>();
+
+            fn radix_byte(&self, index: usize) -> u8 {
+                let bits = self.to_bits();
+                let sign = 1 << (<$unsigned>::BITS - 1);
+                let flipped = if bits & sign != 0 { !bits } else { bits ^ sign };
+                (flipped >> (8 * index)) as u8
+            }
+        }
+    )*};
+}
+
+impl_radix_key_for_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_radix_key_for_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+impl_radix_key_for_float!(f32 => u32, f64 => u64);
+
+impl RadixKey for char {
+    const BYTES: usize = 4;
+
+    fn radix_byte(&self, index: usize) -> u8 {
+        (*self as u32).radix_byte(index)
+    }
+}
+
+// Stable counting sort for keys from a small range.
+// There is a counter for every value between the smallest and the largest key,
+// so the range has to fit in memory: keys spread over a wide range make a huge
+// allocation. Panics if the range spans all of usize.
+// Complexity:
+// Time: O(n + k) where k is the difference between the largest and the smallest key
+// Space: O(n + k)
+pub fn counting_sort<K: Copy + Into<usize>>(input: InPlaceInput<K>) {
+    counting_sort_by_key(input, |&key| key.into());
+}
+
+pub fn counting_sort_by_key<T, F>(input: InPlaceInput<T>, mut key: F)
+where
+    F: FnMut(&T) -> usize,
+{
+    let keys: Vec<usize> = input.iter().map(&mut key).collect();
+    let (Some(&min), Some(&max)) = (keys.iter().min(), keys.iter().max()) else {
+        return;
+    };
+
+    // One counter per key in the range, plus one for the start of the first
+    let counters = (max - min)
+        .checked_add(2)
+        .expect("the range of the keys is too wide for a counting sort");
+    let mut positions = vec![0; counters];
+    for &key in &keys {
+        positions[key - min + 1] += 1;
+    }
+    for i in 1..positions.len() {
+        positions[i] += positions[i - 1];
+    }
+
+    let mut order = vec![0; keys.len()];
+    for (index, &key) in keys.iter().enumerate() {
+        order[positions[key - min]] = index;
+        positions[key - min] += 1;
+    }
+    apply_permutation(input, &mut order);
+}
+
+// LSD radix sort: stable counting sorts by each byte of the key, from the least
+// significant one. Bytes which are the same for all keys are skipped.
+// Complexity:
+// Time: O(n * K::BYTES)
+// Space: O(n)
+pub fn lsd_radix_sort<K: RadixKey>(input: InPlaceInput<K>) {
+    let mut keys = input.to_vec();
+    lsd_radix_sort_with(&mut keys, |&key| key);
+    input.copy_from_slice(&keys);
+}
+
+// Stable LSD radix sort of any elements by a radix key, the elements are only moved
+// by swaps once their final order is known.
+pub fn lsd_radix_sort_by_key<T, K: RadixKey, F>(input: InPlaceInput<T>, mut key: F)
+where
+    F: FnMut(&T) -> K,
+{
+    let mut keyed: Vec<(K, usize)> = input
+        .iter()
+        .enumerate()
+        .map(|(index, element)| (key(element), index))
+        .collect();
+    lsd_radix_sort_with(&mut keyed, |&(key, _)| key);
+
+    let mut order: Vec<usize> = keyed.into_iter().map(|(_, index)| index).collect();
+    apply_permutation(input, &mut order);
+}
+
+fn lsd_radix_sort_with<E: Copy, K: RadixKey, F>(items: &mut Vec<E>, key: F)
+where
+    F: Fn(&E) -> K,
+{
+    let mut buffer = items.clone();
+    for byte in 0..K::BYTES {
+        let mut counts = [0usize; 256];
+        for item in items.iter() {
+            counts[key(item).radix_byte(byte) as usize] += 1;
+        }
+        if counts.contains(&items.len()) {
+            continue;
+        }
+
+        let mut positions = [0usize; 256];
+        for i in 1..256 {
+            positions[i] = positions[i - 1] + counts[i - 1];
+        }
+        for item in items.iter() {
+            let bucket = key(item).radix_byte(byte) as usize;
+            buffer[positions[bucket]] = *item;
+            positions[bucket] += 1;
+        }
+        std::mem::swap(items, &mut buffer);
+    }
+}
+
+// In-place MSD radix sort (American flag sort) of byte strings such as `&str`,
+// `String` or `Vec<u8>`, which results in the lexicographic order.
+// Buckets are processed with an explicit stack, short ones with insertion sort.
+// Complexity:
+// Time: O(n * L) where L is the average length of the distinguishing prefixes
+// Space: O(256 * L) for the stack of buckets, as every level can leave up to
+// 256 of them pending
+pub fn msd_radix_sort<T: AsRef<[u8]>>(input: InPlaceInput<T>) {
+    // Bucket 0 holds the strings which end at the current depth
+    fn bucket<T: AsRef<[u8]>>(item: &T, depth: usize) -> usize {
+        item.as_ref()
+            .get(depth)
+            .map_or(0, |&byte| byte as usize + 1)
+    }
+
+    let mut ranges: Vec<(usize, usize, usize)> = vec![(0, input.len(), 0)];
+    while let Some((left, right, depth)) = ranges.pop() {
+        let range = &mut input[left..right];
+        if range.len() <= MSD_INSERTION_SORT_THRESHOLD {
+            insertion_sort_by(range, &mut |lhs, rhs| {
+                lhs.as_ref()[depth..].cmp(&rhs.as_ref()[depth..])
+            });
+            continue;
+        }
+
+        let mut counts = [0usize; 257];
+        for item in range.iter() {
+            counts[bucket(item, depth)] += 1;
+        }
+        let mut next = [0usize; 257];
+        let mut ends = [0usize; 257];
+        for i in 0..257 {
+            next[i] = if i == 0 { 0 } else { ends[i - 1] };
+            ends[i] = next[i] + counts[i];
+        }
+        let starts = next;
+
+        // Every element is swapped straight into the bucket it belongs to
+        for current in 0..257 {
+            while next[current] < ends[current] {
+                let target = bucket(&range[next[current]], depth);
+                if target == current {
+                    next[current] += 1;
+                } else {
+                    range.swap(next[current], next[target]);
+                    next[target] += 1;
+                }
+            }
+        }
+
+        for i in 1..257 {
+            if counts[i] > 1 {
+                ranges.push((left + starts[i], left + ends[i], depth + 1));
+            }
+        }
+    }
+}
+
+// Bucket sort for floats, which runs in linear time when the values are
+// uniformly distributed between their minimum and maximum.
+// The order is the one of `f64::total_cmp`; infinities and NaNs make the
+// distribution meaningless, so such inputs are sorted by comparisons instead.
+// Complexity:
+// Time: O(n) on average for uniformly distributed input, O(n log n) worst case
+// Space: O(n)
+pub fn bucket_sort(input: InPlaceInput<f64>) {
+    let len = input.len();
+    let (Some(&min), Some(&max)) = (
+        input.iter().min_by(|lhs, rhs| lhs.total_cmp(rhs)),
+        input.iter().max_by(|lhs, rhs| lhs.total_cmp(rhs)),
+    ) else {
+        return;
+    };
+    if !min.is_finite() || !max.is_finite() {
+        intro_sort_by(input, &mut f64::total_cmp);
+        return;
+    }
+
+    let range = max - min;
+    let bucket = |value: f64| {
+        if range > 0.0 {
+            (((value - min) / range * len as f64) as usize).min(len - 1)
+        } else {
+            0
+        }
+    };
+
+    let mut starts = vec![0; len + 1];
+    for &value in input.iter() {
+        starts[bucket(value) + 1] += 1;
+    }
+    for i in 1..starts.len() {
+        starts[i] += starts[i - 1];
+    }
+
+    let mut next = starts.clone();
+    let mut buffer = vec![0.0; len];
+    for &value in input.iter() {
+        let index = bucket(value);
+        buffer[next[index]] = value;
+        next[index] += 1;
+    }
+    input.copy_from_slice(&buffer);
+
+    for window in starts.windows(2) {
+        intro_sort_by(&mut input[window[0]..window[1]], &mut f64::total_cmp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use rand::distr::{Distribution, StandardUniform};
+
+    fn random_vec<T>(len: usize) -> Vec<T>
+    where
+        StandardUniform: Distribution<T>,
+    {
+        let mut rng = rand::rng();
+        (0..len).map(|_| rng.random()).collect()
+    }
+
+    fn check_lsd_radix_sort<K: RadixKey + PartialOrd + std::fmt::Debug>(mut input: Vec<K>) {
+        let mut expected = input.clone();
+        expected.sort_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+        lsd_radix_sort(&mut input);
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn test_lsd_radix_sort_integers() {
+        check_lsd_radix_sort::<u8>(random_vec(1000));
+        check_lsd_radix_sort::<u16>(random_vec(1000));
+        check_lsd_radix_sort::<u32>(random_vec(1000));
+        check_lsd_radix_sort::<u64>(random_vec(1000));
+        check_lsd_radix_sort::<u128>(random_vec(1000));
+        check_lsd_radix_sort(
+            random_vec::<u32>(1000)
+                .into_iter()
+                .map(|value| value as usize)
+                .collect(),
+        );
+        check_lsd_radix_sort::<i8>(random_vec(1000));
+        check_lsd_radix_sort::<i32>(random_vec(1000));
+        check_lsd_radix_sort::<i64>(random_vec(1000));
+        check_lsd_radix_sort::<i128>(random_vec(1000));
+        check_lsd_radix_sort(vec![i64::MAX, 0, i64::MIN, -1, 1, i64::MIN + 1]);
+        check_lsd_radix_sort::<u32>(vec![]);
+        check_lsd_radix_sort(vec![42u32; 10]);
+        check_lsd_radix_sort(vec!['z', 'a', 'é', '€', 'A']);
+    }
+
+    #[test]
+    fn test_lsd_radix_sort_floats() {
+        let mut rng = rand::rng();
+        let floats: Vec<f64> = (0..1000).map(|_| rng.random_range(-1e6..1e6)).collect();
+        check_lsd_radix_sort(floats);
+        let floats: Vec<f32> = (0..1000).map(|_| rng.random_range(-10.0..10.0)).collect();
+        check_lsd_radix_sort(floats);
+
+        let mut special = vec![
+            f64::NAN,
+            f64::INFINITY,
+            -0.0,
+            1.5,
+            f64::NEG_INFINITY,
+            0.0,
+            -f64::MIN_POSITIVE,
+            f64::MIN_POSITIVE,
+            -2.5,
+        ];
+        let mut expected = special.clone();
+        expected.sort_by(f64::total_cmp);
+        lsd_radix_sort(&mut special);
+        assert_eq!(
+            special
+                .iter()
+                .map(|value| value.to_bits())
+                .collect::<Vec<u64>>(),
+            expected
+                .iter()
+                .map(|value| value.to_bits())
+                .collect::<Vec<u64>>()
+        );
+    }
+
+    #[test]
+    fn test_lsd_radix_sort_by_key() {
+        let mut records = vec![("c", 3u32), ("a", 1), ("b", 3), ("d", 0), ("e", 1)];
+        lsd_radix_sort_by_key(&mut records, |record| record.1);
+        assert_eq!(records, [("d", 0), ("a", 1), ("e", 1), ("c", 3), ("b", 3)]);
+
+        let mut owned: Vec<String> = vec!["ccc".into(), "a".into(), "bb".into()];
+        lsd_radix_sort_by_key(&mut owned, |word| word.len());
+        assert_eq!(owned, ["a", "bb", "ccc"]);
+    }
+
+    // A user type opting into radix sorting: ordered by priority descending, then by id
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Ticket {
+        priority: u8,
+        id: u16,
+    }
+
+    impl RadixKey for Ticket {
+        const BYTES: usize = 3;
+
+        fn radix_byte(&self, index: usize) -> u8 {
+            match index {
+                0 | 1 => self.id.radix_byte(index),
+                _ => u8::MAX - self.priority,
+            }
+        }
+    }
+
+    #[test]
+    fn test_user_radix_key() {
+        let mut tickets: Vec<Ticket> = (0..500u16)
+            .rev()
+            .map(|id| Ticket {
+                priority: (id % 4) as u8,
+                id,
+            })
+            .collect();
+        lsd_radix_sort(&mut tickets);
+        for pair in tickets.windows(2) {
+            let (lhs, rhs) = (pair[0], pair[1]);
+            assert!(
+                lhs.priority > rhs.priority || (lhs.priority == rhs.priority && lhs.id < rhs.id)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "too wide")]
+    fn test_counting_sort_full_range() {
+        let mut keys = [usize::MAX, 0];
+        counting_sort_by_key(&mut keys, |&key| key);
+    }
+
+    #[test]
+    fn test_counting_sort() {
+        let mut values: Vec<u8> = random_vec(1000);
+        let mut expected = values.clone();
+        expected.sort();
+        counting_sort(&mut values);
+        assert_eq!(values, expected);
+
+        let mut empty: [u16; 0] = [];
+        counting_sort(&mut empty);
+
+        // Keys away from zero only need counters for their own range
+        let mut records = vec![
+            (1_000_002, 'a'),
+            (1_000_000, 'b'),
+            (1_000_002, 'c'),
+            (1_000_001, 'd'),
+        ];
+        counting_sort_by_key(&mut records, |record| record.0);
+        assert_eq!(
+            records,
+            [
+                (1_000_000, 'b'),
+                (1_000_001, 'd'),
+                (1_000_002, 'a'),
+                (1_000_002, 'c')
+            ]
+        );
+    }
+
+    #[test]
+    fn test_msd_radix_sort() {
+        let mut rng = rand::rng();
+        let mut words: Vec<Vec<u8>> = (0..2000)
+            .map(|_| {
+                let len = rng.random_range(0..12);
+                (0..len).map(|_| rng.random_range(b'a'..=b'd')).collect()
+            })
+            .collect();
+        let mut expected = words.clone();
+        expected.sort();
+        msd_radix_sort(&mut words);
+        assert_eq!(words, expected);
+
+        let mut names = vec![
+            "Zoë",
+            "zoe",
+            "Ångström",
+            "",
+            "Anders",
+            "anders",
+            "Zoe",
+            "Anna",
+        ];
+        let mut expected = names.clone();
+        expected.sort();
+        msd_radix_sort(&mut names);
+        assert_eq!(names, expected);
+
+        let mut shared_prefixes: Vec<String> = (0..100)
+            .rev()
+            .map(|i| format!("{}{i}", "x".repeat(500)))
+            .collect();
+        let mut expected = shared_prefixes.clone();
+        expected.sort();
+        msd_radix_sort(&mut shared_prefixes);
+        assert_eq!(shared_prefixes, expected);
+    }
+
+    #[test]
+    fn test_bucket_sort() {
+        let mut rng = rand::rng();
+        let mut uniform: Vec<f64> = (0..1000).map(|_| rng.random()).collect();
+        let mut expected = uniform.clone();
+        expected.sort_by(f64::total_cmp);
+        bucket_sort(&mut uniform);
+        assert_eq!(uniform, expected);
+
+        let mut skewed: Vec<f64> = (0..1000)
+            .map(|i| (i % 10) as f64 * 1e-3 + 1e9 * (i % 2) as f64)
+            .collect();
+        let mut expected = skewed.clone();
+        expected.sort_by(f64::total_cmp);
+        bucket_sort(&mut skewed);
+        assert_eq!(skewed, expected);
+
+        let mut equal = vec![2.5; 100];
+        bucket_sort(&mut equal);
+        assert_eq!(equal, vec![2.5; 100]);
+
+        let mut special = vec![1.0, f64::INFINITY, -3.0, f64::NEG_INFINITY, 0.5];
+        bucket_sort(&mut special);
+        assert_eq!(special, [f64::NEG_INFINITY, -3.0, 0.5, 1.0, f64::INFINITY]);
+
+        let mut empty: [f64; 0] = [];
+        bucket_sort(&mut empty);
+    }
+}
@@ -0,0 +1,312 @@
+use super::{
+    InPlaceInput, choose_pivot, heap_sort_by, insertion_sort_by, intro_sort_by, sift_down_by,
+    three_way_partition,
+};
+use std::cmp::Ordering;
+
+const SELECT_INSERTION_SORT_THRESHOLD: usize = 16;
+
+// Reorders the input so that input[n] holds the element which would be there if
+// the input were sorted, everything before it is not greater and everything
+// after it is not less.
+// Introselect: quickselect with the three-way partitioning of `quick_sort` and
+// median-of-three / ninther pivots, which switches to the median of medians as
+// pivot once two partitioning steps in a row fail to halve the range.
+// Panics if n is out of bounds.
+// Complexity:
+// Time: O(n) in the worst case
+// Space: O(log n) for the recursion of the median of medians
+pub fn nth_element<T: Ord>(input: InPlaceInput<T>, n: usize) {
+    nth_element_by(input, n, &mut T::cmp);
+}
+
+pub fn nth_element_by_key<T, K: Ord, F>(input: InPlaceInput<T>, n: usize, f: &mut F)
+where
+    F: FnMut(&T) -> K,
+{
+    nth_element_by(input, n, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn nth_element_by<T, F>(input: InPlaceInput<T>, n: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    assert!(
+        n < input.len(),
+        "index {n} is out of bounds for length {}",
+        input.len()
+    );
+
+    let (mut left, mut right) = (0, input.len());
+    let mut use_median_of_medians = false;
+    let mut steps = 0;
+    let mut checkpoint_len = input.len();
+    loop {
+        let range = &mut input[left..right];
+        if range.len() <= SELECT_INSERTION_SORT_THRESHOLD {
+            insertion_sort_by(range, compare);
+            return;
+        }
+
+        let pivot_index = if use_median_of_medians {
+            median_of_medians(range, compare)
+        } else {
+            choose_pivot(range, compare)
+        };
+        let (less_end, greater_start) = three_way_partition(range, pivot_index, compare);
+        let target = n - left;
+        if target < less_end {
+            right = left + less_end;
+        } else if target >= greater_start {
+            left += greater_start;
+        } else {
+            return;
+        }
+
+        steps += 1;
+        if steps % 2 == 0 {
+            use_median_of_medians |= right - left > checkpoint_len / 2;
+            checkpoint_len = right - left;
+        }
+    }
+}
+
+// Quickselect: the element which would be at index n if the input were sorted,
+// the input is left reordered as by `nth_element`
+pub fn quickselect<'a, T: Ord>(input: InPlaceInput<'a, T>, n: usize) -> &'a T {
+    nth_element(input, n);
+    &input[n]
+}
+
+pub fn quickselect_by<'a, T, F>(input: InPlaceInput<'a, T>, n: usize, compare: &mut F) -> &'a T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    nth_element_by(input, n, compare);
+    &input[n]
+}
+
+// Sorts each group of five, gathers the group medians at the front and selects
+// their median, which is guaranteed to have at least 30% of the elements on
+// either side. Returns its index.
+fn median_of_medians<T, F>(input: InPlaceInput<T>, compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let groups = input.len() / 5;
+    if groups == 0 {
+        insertion_sort_by(input, compare);
+        return input.len() / 2;
+    }
+    for group in 0..groups {
+        insertion_sort_by(&mut input[group * 5..group * 5 + 5], compare);
+        input.swap(group, group * 5 + 2);
+    }
+    nth_element_by(&mut input[..groups], groups / 2, compare);
+    groups / 2
+}
+
+// Sorts the k smallest elements into input[..k], the rest is left in no particular order.
+// Complexity:
+// Time: O(n + k log k)
+// Space: O(log n)
+pub fn partial_sort<T: Ord>(input: InPlaceInput<T>, k: usize) {
+    partial_sort_by(input, k, &mut T::cmp);
+}
+
+pub fn partial_sort_by_key<T, K: Ord, F>(input: InPlaceInput<T>, k: usize, f: &mut F)
+where
+    F: FnMut(&T) -> K,
+{
+    partial_sort_by(input, k, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn partial_sort_by<T, F>(input: InPlaceInput<T>, k: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if k == 0 {
+        return;
+    }
+    if k >= input.len() {
+        intro_sort_by(input, compare);
+        return;
+    }
+    nth_element_by(input, k - 1, compare);
+    intro_sort_by(&mut input[..k - 1], compare);
+}
+
+// The k smallest items of a stream in ascending order, keeping only a bounded
+// max-heap of the best k items seen so far.
+// Complexity:
+// Time: O(n log k)
+// Space: O(k)
+pub fn top_k<I>(items: I, k: usize) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Ord,
+{
+    top_k_by(items, k, &mut I::Item::cmp)
+}
+
+pub fn top_k_by_key<I, K: Ord, F>(items: I, k: usize, f: &mut F) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item) -> K,
+{
+    top_k_by(items, k, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)))
+}
+
+pub fn top_k_by<I, F>(items: I, k: usize, compare: &mut F) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    if k == 0 {
+        return Vec::new();
+    }
+
+    let mut items = items.into_iter();
+    let mut heap: Vec<I::Item> = items.by_ref().take(k).collect();
+    for node in (0..heap.len() / 2).rev() {
+        sift_down_by(&mut heap, node, compare);
+    }
+    for item in items {
+        if compare(&item, &heap[0]) == Ordering::Less {
+            heap[0] = item;
+            sift_down_by(&mut heap, 0, compare);
+        }
+    }
+    heap_sort_by(&mut heap, compare);
+    heap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use rand::seq::SliceRandom;
+
+    fn random_input(len: usize, distinct: i32) -> Vec<i32> {
+        let mut rng = rand::rng();
+        (0..len).map(|_| rng.random_range(0..distinct)).collect()
+    }
+
+    fn count_comparisons(input: &mut [i32], n: usize) -> usize {
+        let mut comparisons = 0;
+        nth_element_by(input, n, &mut |lhs, rhs| {
+            comparisons += 1;
+            lhs.cmp(rhs)
+        });
+        comparisons
+    }
+
+    #[test]
+    fn test_nth_element_against_sort() {
+        for len in [1, 2, 5, 16, 17, 100, 1000] {
+            for distinct in [1, 3, 1000] {
+                let input = random_input(len, distinct);
+                let mut sorted = input.clone();
+                sorted.sort();
+                for n in [0, len / 3, len / 2, len - 1] {
+                    let mut arr = input.clone();
+                    nth_element(&mut arr, n);
+                    assert_eq!(arr[n], sorted[n]);
+                    assert!(arr[..n].iter().all(|value| *value <= arr[n]));
+                    assert!(arr[n + 1..].iter().all(|value| *value >= arr[n]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_quickselect() {
+        let mut arr = [9, 1, 8, 2, 7, 3, 6, 4, 5];
+        assert_eq!(*quickselect(&mut arr, 4), 5);
+        assert_eq!(
+            *quickselect_by(&mut arr, 0, &mut |lhs, rhs| rhs.cmp(lhs)),
+            9
+        );
+
+        let mut records = [("a", 3), ("b", 1), ("c", 2)];
+        nth_element_by_key(&mut records, 0, &mut |record| record.1);
+        assert_eq!(records[0], ("b", 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_nth_element_out_of_bounds() {
+        let mut arr = [1, 2, 3];
+        nth_element(&mut arr, 3);
+    }
+
+    #[test]
+    fn test_linear_comparisons() {
+        let len: usize = 1 << 16;
+        let half = len as i32 / 2;
+        let inputs: [Vec<i32>; 5] = [
+            (0..len as i32).collect(),
+            (0..len as i32).rev().collect(),
+            vec![1; len],
+            (0..half).chain((0..half).rev()).collect(),
+            random_input(len, i32::MAX),
+        ];
+        for input in inputs {
+            for n in [0, len / 2, len - 1] {
+                let comparisons = count_comparisons(&mut input.clone(), n);
+                assert!(comparisons <= 30 * len, "{comparisons} comparisons");
+            }
+        }
+    }
+
+    #[test]
+    fn test_median_of_medians() {
+        let mut rng = rand::rng();
+        let mut input: Vec<i32> = (0..1000).collect();
+        input.shuffle(&mut rng);
+        let index = median_of_medians(&mut input, &mut i32::cmp);
+        let rank = input[index];
+        assert!((300..700).contains(&rank), "rank {rank}");
+    }
+
+    #[test]
+    fn test_partial_sort() {
+        for k in [0, 1, 5, 99, 100, 150] {
+            let input = random_input(100, 20);
+            let mut sorted = input.clone();
+            sorted.sort();
+            let mut arr = input.clone();
+            partial_sort(&mut arr, k);
+            let k = k.min(100);
+            assert_eq!(arr[..k], sorted[..k]);
+            let mut rest = arr[k..].to_vec();
+            rest.sort();
+            assert_eq!(rest, sorted[k..]);
+        }
+
+        let mut words = ["pear", "fig", "banana", "kiwi"];
+        partial_sort_by_key(&mut words, 2, &mut |word| word.len());
+        // "pear" and "kiwi" tie, and the sort is not stable
+        assert_eq!(words[0], "fig");
+        assert_eq!(words[1].len(), 4);
+    }
+
+    #[test]
+    fn test_top_k() {
+        let input = random_input(1000, 500);
+        let mut sorted = input.clone();
+        sorted.sort();
+        for k in [0, 1, 10, 999, 1000, 2000] {
+            assert_eq!(top_k(input.iter().copied(), k), sorted[..k.min(1000)]);
+        }
+
+        let largest = top_k_by(1..=100, 3, &mut |lhs: &i32, rhs: &i32| rhs.cmp(lhs));
+        assert_eq!(largest, [100, 99, 98]);
+
+        let shortest = top_k_by_key(["banana", "fig", "apple", "kiwi"], 2, &mut |word| {
+            word.len()
+        });
+        assert_eq!(shortest, ["fig", "kiwi"]);
+        assert!(top_k(Vec::<i32>::new(), 3).is_empty());
+    }
+}
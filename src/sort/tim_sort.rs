@@ -0,0 +1,368 @@
+use super::{InPlaceInput, apply_permutation, binary_insertion_sort_from};
+use crate::binary_search::partition_point;
+use std::cmp::Ordering;
+
+// Inputs shorter than this are a single run extended by binary insertion
+const MIN_MERGE: usize = 32;
+// Wins in a row after which a merge switches to galloping
+const MIN_GALLOP: usize = 7;
+
+// Adaptive natural merge sort in the style of TimSort.
+// The input is split into ascending or strictly descending runs (the latter
+// are reversed), short runs are extended to `min_run` by binary insertion, and
+// runs are merged from a stack which keeps TimSort's length invariants so
+// merges stay balanced. Merges gallop through long stretches taken from one
+// side. The sort is stable and needs only n - 1 comparisons on sorted input.
+// Complexity:
+// Time: O(n log n), O(n) for input made of few runs
+// Space: O(n) indices for the merges
+pub fn tim_sort<T: Ord>(input: InPlaceInput<T>) {
+    tim_sort_by(input, &mut T::cmp);
+}
+
+pub fn tim_sort_by_key<T, K: Ord, F>(input: InPlaceInput<T>, f: &mut F)
+where
+    F: FnMut(&T) -> K,
+{
+    tim_sort_by(input, &mut |lhs, rhs| f(lhs).cmp(&f(rhs)));
+}
+
+pub fn tim_sort_by<T, F>(input: InPlaceInput<T>, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = input.len();
+    if len < 2 {
+        return;
+    }
+
+    let min_run = min_run_length(len);
+    let mut merger = Merger::default();
+    // (start, length) of the runs waiting to be merged
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    let mut start = 0;
+    while start < len {
+        let mut run_len = make_ascending_run(&mut input[start..], compare);
+        if run_len < min_run {
+            let forced_len = min_run.min(len - start);
+            binary_insertion_sort_from(&mut input[start..start + forced_len], run_len, compare);
+            run_len = forced_len;
+        }
+        runs.push((start, run_len));
+        start += run_len;
+
+        // Restore the invariants on the top of the stack:
+        // runs[n - 2].len > runs[n - 1].len + runs[n].len and runs[n - 1].len > runs[n].len
+        while runs.len() > 1 {
+            let mut n = runs.len() - 2;
+            if (n > 0 && runs[n - 1].1 <= runs[n].1 + runs[n + 1].1)
+                || (n > 1 && runs[n - 2].1 <= runs[n - 1].1 + runs[n].1)
+            {
+                if runs[n - 1].1 < runs[n + 1].1 {
+                    n -= 1;
+                }
+            } else if runs[n].1 > runs[n + 1].1 {
+                break;
+            }
+            merger.merge_at(input, &mut runs, n, compare);
+        }
+    }
+
+    while runs.len() > 1 {
+        let mut n = runs.len() - 2;
+        if n > 0 && runs[n - 1].1 < runs[n + 1].1 {
+            n -= 1;
+        }
+        merger.merge_at(input, &mut runs, n, compare);
+    }
+}
+
+// Stable merge of the sorted input[..mid] and input[mid..] with galloping
+pub(super) fn merge_runs_by<T, F>(input: InPlaceInput<T>, mid: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    Merger::default().merge(input, mid, compare);
+}
+
+// A run length in [MIN_MERGE / 2, MIN_MERGE] such that len / min_run is
+// a power of two or slightly less than one
+fn min_run_length(mut len: usize) -> usize {
+    let mut any_bit_shifted_out = 0;
+    while len >= MIN_MERGE {
+        any_bit_shifted_out |= len & 1;
+        len >>= 1;
+    }
+    len + any_bit_shifted_out
+}
+
+// Finds the run at the start of the input and returns its length.
+// A strictly descending run is reversed, which cannot break stability.
+fn make_ascending_run<T, F>(input: InPlaceInput<T>, compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if input.len() < 2 {
+        return input.len();
+    }
+
+    let mut end = 2;
+    if compare(&input[1], &input[0]) == Ordering::Less {
+        while end < input.len() && compare(&input[end], &input[end - 1]) == Ordering::Less {
+            end += 1;
+        }
+        input[..end].reverse();
+    } else {
+        while end < input.len() && compare(&input[end], &input[end - 1]) != Ordering::Less {
+            end += 1;
+        }
+    }
+    end
+}
+
+// Exponential search from the start of `sorted` narrowed down by a binary
+// search: the number of leading elements for which `goes_before` holds.
+// Costs O(log k) comparisons for a result of k.
+fn gallop<T, P>(sorted: &[T], mut goes_before: P) -> usize
+where
+    P: FnMut(&T) -> bool,
+{
+    let mut bound = 1;
+    while bound <= sorted.len() && goes_before(&sorted[bound - 1]) {
+        bound *= 2;
+    }
+    let known = bound / 2;
+    let end = (bound - 1).min(sorted.len());
+    known + partition_point(&sorted[known..end], goes_before)
+}
+
+struct Merger {
+    min_gallop: usize,
+    order: Vec<usize>,
+}
+
+impl Default for Merger {
+    fn default() -> Self {
+        Merger {
+            min_gallop: MIN_GALLOP,
+            order: Vec::new(),
+        }
+    }
+}
+
+impl Merger {
+    // Merges runs[n] with runs[n + 1]
+    fn merge_at<T, F>(
+        &mut self,
+        input: InPlaceInput<T>,
+        runs: &mut Vec<(usize, usize)>,
+        n: usize,
+        compare: &mut F,
+    ) where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let (start, lhs_len) = runs[n];
+        let rhs_len = runs[n + 1].1;
+        self.merge(
+            &mut input[start..start + lhs_len + rhs_len],
+            lhs_len,
+            compare,
+        );
+        runs[n].1 += rhs_len;
+        runs.remove(n + 1);
+    }
+
+    // Merges the sorted input[..mid] and input[mid..]. The merged order is
+    // recorded as indices first and then applied to the elements with swaps.
+    fn merge<T, F>(&mut self, input: InPlaceInput<T>, mid: usize, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let (lhs, rhs) = input.split_at(mid);
+        if lhs.is_empty() || rhs.is_empty() {
+            return;
+        }
+
+        // Elements of the left run not greater than the first right element, and
+        // elements of the right run not less than the last left one, stay in place
+        let lhs_start = partition_point(lhs, |value| compare(value, &rhs[0]) != Ordering::Greater);
+        if lhs_start == lhs.len() {
+            return;
+        }
+        let rhs_end = partition_point(rhs, |value| {
+            compare(value, &lhs[lhs.len() - 1]) == Ordering::Less
+        });
+        let lhs = &lhs[lhs_start..];
+        let rhs = &rhs[..rhs_end];
+
+        // Indices are relative to the start of `lhs`, `rhs` begins at lhs.len()
+        let order = &mut self.order;
+        order.clear();
+        let (mut i, mut j) = (0, 0);
+        let (mut lhs_wins, mut rhs_wins) = (0, 0);
+        while i < lhs.len() && j < rhs.len() {
+            if compare(&rhs[j], &lhs[i]) == Ordering::Less {
+                order.push(lhs.len() + j);
+                j += 1;
+                rhs_wins += 1;
+                lhs_wins = 0;
+            } else {
+                order.push(i);
+                i += 1;
+                lhs_wins += 1;
+                rhs_wins = 0;
+            }
+            if lhs_wins < self.min_gallop && rhs_wins < self.min_gallop {
+                continue;
+            }
+
+            // Galloping: take whole stretches from one side while they are long
+            while i < lhs.len() && j < rhs.len() {
+                let lhs_count = gallop(&lhs[i..], |value| {
+                    compare(value, &rhs[j]) != Ordering::Greater
+                });
+                order.extend(i..i + lhs_count);
+                i += lhs_count;
+                if i == lhs.len() {
+                    break;
+                }
+                order.push(lhs.len() + j);
+                j += 1;
+                if j == rhs.len() {
+                    break;
+                }
+
+                let rhs_count =
+                    gallop(&rhs[j..], |value| compare(value, &lhs[i]) == Ordering::Less);
+                order.extend((j..j + rhs_count).map(|index| lhs.len() + index));
+                j += rhs_count;
+                if j == rhs.len() {
+                    break;
+                }
+                order.push(i);
+                i += 1;
+
+                if lhs_count < MIN_GALLOP && rhs_count < MIN_GALLOP {
+                    break;
+                }
+                self.min_gallop = self.min_gallop.saturating_sub(1).max(1);
+            }
+            // Leaving the galloping mode makes it harder to enter it again
+            self.min_gallop += 2;
+            lhs_wins = 0;
+            rhs_wins = 0;
+        }
+        order.extend(i..lhs.len());
+        order.extend((j..rhs.len()).map(|index| lhs.len() + index));
+
+        let merged_len = order.len();
+        apply_permutation(&mut input[lhs_start..lhs_start + merged_len], order);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::tests::{assert_stable, check_sort_battery};
+    use rand::Rng;
+    use rand::seq::SliceRandom;
+
+    fn count_comparisons(input: &mut [i32]) -> usize {
+        let mut comparisons = 0;
+        tim_sort_by(input, &mut |lhs, rhs| {
+            comparisons += 1;
+            lhs.cmp(rhs)
+        });
+        comparisons
+    }
+
+    #[test]
+    fn test_tim_sort() {
+        check_sort_battery(tim_sort);
+        assert_stable(|input| tim_sort_by_key(input, &mut |element| element.0));
+
+        let mut rng = rand::rng();
+        let mut arr: Vec<i32> = (0..10_000).collect();
+        arr.shuffle(&mut rng);
+        tim_sort(&mut arr);
+        assert_eq!(arr, (0..10_000).collect::<Vec<i32>>());
+
+        let mut words = vec!["pear", "fig", "apple", "kiwi"];
+        tim_sort_by(&mut words, &mut |lhs, rhs| rhs.cmp(lhs));
+        assert_eq!(words, ["pear", "kiwi", "fig", "apple"]);
+    }
+
+    #[test]
+    fn test_linear_comparisons_on_runs() {
+        let len = 100_000;
+
+        let mut sorted: Vec<i32> = (0..len).collect();
+        assert_eq!(count_comparisons(&mut sorted), len as usize - 1);
+
+        let mut reversed: Vec<i32> = (0..len).rev().collect();
+        assert_eq!(count_comparisons(&mut reversed), len as usize - 1);
+        assert_eq!(reversed, (0..len).collect::<Vec<i32>>());
+
+        let mut equal = vec![3; len as usize];
+        assert_eq!(count_comparisons(&mut equal), len as usize - 1);
+
+        // Two ascending runs need a single galloping merge
+        let mut two_runs: Vec<i32> = (0..len).filter(|i| i % 2 == 0).collect();
+        two_runs.extend((0..len).filter(|i| i % 2 == 1));
+        let comparisons = count_comparisons(&mut two_runs);
+        assert_eq!(two_runs, (0..len).collect::<Vec<i32>>());
+        assert!(comparisons <= 3 * len as usize, "{comparisons} comparisons");
+
+        // A sorted log with a few new entries appended
+        let mut appended: Vec<i32> = (0..len).map(|i| i * 10).collect();
+        appended.extend([5, 55_555, 12]);
+        let comparisons = count_comparisons(&mut appended);
+        assert!(appended.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(comparisons <= 2 * len as usize, "{comparisons} comparisons");
+    }
+
+    #[test]
+    fn test_nearly_sorted() {
+        let mut rng = rand::rng();
+        let len = 10_000;
+        let mut arr: Vec<i32> = (0..len).collect();
+        for _ in 0..20 {
+            let i = rng.random_range(0..len as usize);
+            let j = rng.random_range(0..len as usize);
+            arr.swap(i, j);
+        }
+        tim_sort(&mut arr);
+        assert_eq!(arr, (0..len).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn test_gallop() {
+        let sorted = [1, 2, 3, 5, 8, 13, 21, 34, 55, 89];
+        for key in 0..100 {
+            let expected = sorted.iter().filter(|&&value| value < key).count();
+            assert_eq!(gallop(&sorted, |&value| value < key), expected);
+        }
+        assert_eq!(gallop(&[] as &[i32], |_| true), 0);
+    }
+
+    #[test]
+    fn test_min_run_length() {
+        assert_eq!(min_run_length(10), 10);
+        assert_eq!(min_run_length(64), 16);
+        assert_eq!(min_run_length(65), 17);
+        for len in 32..10_000 {
+            let min_run = min_run_length(len);
+            assert!((MIN_MERGE / 2..=MIN_MERGE).contains(&min_run));
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut arr = [(1, 'a'), (4, 'b'), (4, 'c'), (0, 'd'), (4, 'e'), (9, 'f')];
+        Merger::default().merge(&mut arr, 3, &mut |lhs, rhs| lhs.0.cmp(&rhs.0));
+        assert_eq!(
+            arr,
+            [(0, 'd'), (1, 'a'), (4, 'b'), (4, 'c'), (4, 'e'), (9, 'f')]
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use crate::levenshtein_distance;
+use std::collections::HashMap;
+
+// Distances between strings other than the Levenshtein distance, all counted in
+// chars like `levenshtein_distance::calculate`. Each comes with a similarity
+// normalized to [0, 1], where 1 means equal strings and two empty strings are
+// equal.
+
+// Levenshtein distance where swapping two adjacent chars is a single edit, but
+// no substring is edited more than once: "ca" -> "abc" takes 3 edits, since
+// "ca" -> "ac" -> "abc" would insert between the transposed chars.
+// Complexity:
+// Time: O(m * n)
+// Space: O(n) for three rows of the DP table
+pub fn optimal_string_alignment(lhs: &str, rhs: &str) -> usize {
+    let lhs: Vec<char> = lhs.chars().collect();
+    let rhs: Vec<char> = rhs.chars().collect();
+
+    let mut before_previous: Vec<usize> = vec![0; rhs.len() + 1];
+    let mut previous: Vec<usize> = (0..=rhs.len()).collect();
+    let mut current: Vec<usize> = vec![0; rhs.len() + 1];
+    for i in 1..=lhs.len() {
+        current[0] = i;
+        for j in 1..=rhs.len() {
+            let cost = usize::from(lhs[i - 1] != rhs[j - 1]);
+            current[j] = (previous[j - 1] + cost)
+                .min(previous[j] + 1)
+                .min(current[j - 1] + 1);
+            if i > 1 && j > 1 && lhs[i - 1] == rhs[j - 2] && lhs[i - 2] == rhs[j - 1] {
+                current[j] = current[j].min(before_previous[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before_previous, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[rhs.len()]
+}
+
+// Levenshtein distance where swapping two adjacent chars is a single edit, with
+// no restriction on editing between them: "ca" -> "abc" takes 2 edits.
+// Lowrance and Wagner's algorithm: a transposition of lhs[k] and lhs[i] with
+// rhs[l] and rhs[j] costs the chars deleted and inserted between them plus one,
+// and only the last occurrences of the chars before (i, j) need to be tried.
+// Complexity:
+// Time: O(m * n)
+// Space: O(m * n) as transpositions can reach back to any row
+pub fn damerau_levenshtein(lhs: &str, rhs: &str) -> usize {
+    let lhs: Vec<char> = lhs.chars().collect();
+    let rhs: Vec<char> = rhs.chars().collect();
+
+    // The table is shifted by one: row and column 0 hold a value larger than any
+    // distance so that transpositions with no earlier occurrence are never taken
+    let infinity = lhs.len() + rhs.len();
+    let mut table = vec![vec![infinity; rhs.len() + 2]; lhs.len() + 2];
+    for (i, row) in table.iter_mut().enumerate().skip(1) {
+        row[1] = i - 1;
+    }
+    for (j, cell) in table[1].iter_mut().enumerate().skip(1) {
+        *cell = j - 1;
+    }
+
+    // The last row of lhs holding each char
+    let mut last_row: HashMap<char, usize> = HashMap::new();
+    for i in 1..=lhs.len() {
+        // The last column of rhs in this row whose char matched lhs[i - 1]
+        let mut last_column = 0;
+        for j in 1..=rhs.len() {
+            let k = last_row.get(&rhs[j - 1]).copied().unwrap_or(0);
+            let l = last_column;
+            let cost = if lhs[i - 1] == rhs[j - 1] {
+                last_column = j;
+                0
+            } else {
+                1
+            };
+            table[i + 1][j + 1] = (table[i][j] + cost)
+                .min(table[i + 1][j] + 1)
+                .min(table[i][j + 1] + 1)
+                .min(table[k][l] + (i - k - 1) + 1 + (j - l - 1));
+        }
+        last_row.insert(lhs[i - 1], i);
+    }
+    table[lhs.len() + 1][rhs.len() + 1]
+}
+
+// The number of positions holding different chars, or None if the strings
+// differ in length.
+// Complexity:
+// Time: O(n)
+// Space: O(1)
+pub fn hamming(lhs: &str, rhs: &str) -> Option<usize> {
+    let mut lhs = lhs.chars();
+    let mut rhs = rhs.chars();
+    let mut distance = 0;
+    loop {
+        match (lhs.next(), rhs.next()) {
+            (Some(l), Some(r)) => distance += usize::from(l != r),
+            (None, None) => return Some(distance),
+            _ => return None,
+        }
+    }
+}
+
+// The number of chars to delete and insert, with no substitutions, to turn lhs
+// into rhs: the chars outside a longest common subsequence.
+// Complexity:
+// Time: O(m * n)
+// Space: O(n)
+pub fn lcs_distance(lhs: &str, rhs: &str) -> usize {
+    let lhs: Vec<char> = lhs.chars().collect();
+    let rhs: Vec<char> = rhs.chars().collect();
+    lhs.len() + rhs.len() - 2 * longest_common_subsequence(&lhs, &rhs)
+}
+
+fn longest_common_subsequence(lhs: &[char], rhs: &[char]) -> usize {
+    let mut previous = vec![0; rhs.len() + 1];
+    let mut current = vec![0; rhs.len() + 1];
+    for l in lhs {
+        for (j, r) in rhs.iter().enumerate() {
+            current[j + 1] = if l == r {
+                previous[j] + 1
+            } else {
+                previous[j + 1].max(current[j])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[rhs.len()]
+}
+
+// Jaro similarity: chars match if they are equal and at most
+// max(m, n) / 2 - 1 positions apart, and t is half the number of matched chars
+// that come in a different order in the two strings. With c matches, the
+// similarity is (c / m + c / n + (c - t) / c) / 3.
+// Complexity:
+// Time: O(m * n) in the worst case, O(m + n) with a bounded window
+// Space: O(m + n)
+pub fn jaro_similarity(lhs: &str, rhs: &str) -> f64 {
+    let lhs: Vec<char> = lhs.chars().collect();
+    let rhs: Vec<char> = rhs.chars().collect();
+    if lhs.is_empty() && rhs.is_empty() {
+        return 1.0;
+    }
+
+    let window = (lhs.len().max(rhs.len()) / 2).saturating_sub(1);
+    let mut lhs_matched = vec![false; lhs.len()];
+    let mut rhs_matched = vec![false; rhs.len()];
+    let mut matches = 0;
+    for (i, l) in lhs.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(rhs.len());
+        for j in start..end {
+            if !rhs_matched[j] && rhs[j] == *l {
+                lhs_matched[i] = true;
+                rhs_matched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+
+    let lhs_order = lhs
+        .iter()
+        .zip(&lhs_matched)
+        .filter(|(_, matched)| **matched);
+    let rhs_order = rhs
+        .iter()
+        .zip(&rhs_matched)
+        .filter(|(_, matched)| **matched);
+    let out_of_order = lhs_order
+        .zip(rhs_order)
+        .filter(|((l, _), (r, _))| l != r)
+        .count();
+
+    let matches = matches as f64;
+    let transpositions = out_of_order as f64 / 2.0;
+    (matches / lhs.len() as f64 + matches / rhs.len() as f64 + (matches - transpositions) / matches)
+        / 3.0
+}
+
+// Jaro-Winkler similarity: the Jaro similarity s raised for strings sharing a
+// prefix of length p (counting at most the first JARO_WINKLER_MAX_PREFIX chars)
+// to s + p * JARO_WINKLER_PREFIX_SCALE * (1 - s).
+// Complexity: as `jaro_similarity`
+pub fn jaro_winkler_similarity(lhs: &str, rhs: &str) -> f64 {
+    let jaro = jaro_similarity(lhs, rhs);
+    let prefix = lhs
+        .chars()
+        .zip(rhs.chars())
+        .take(JARO_WINKLER_MAX_PREFIX)
+        .take_while(|(l, r)| l == r)
+        .count();
+    jaro + prefix as f64 * JARO_WINKLER_PREFIX_SCALE * (1.0 - jaro)
+}
+
+// Winkler's values, which keep the similarity at most 1
+pub const JARO_WINKLER_PREFIX_SCALE: f64 = 0.1;
+pub const JARO_WINKLER_MAX_PREFIX: usize = 4;
+
+// The similarities of the edit distances divide by the largest possible
+// distance for strings of those lengths
+
+pub fn levenshtein_similarity(lhs: &str, rhs: &str) -> f64 {
+    normalize(
+        levenshtein_distance::calculate(lhs, rhs),
+        longer_length(lhs, rhs),
+    )
+}
+
+pub fn optimal_string_alignment_similarity(lhs: &str, rhs: &str) -> f64 {
+    normalize(optimal_string_alignment(lhs, rhs), longer_length(lhs, rhs))
+}
+
+pub fn damerau_levenshtein_similarity(lhs: &str, rhs: &str) -> f64 {
+    normalize(damerau_levenshtein(lhs, rhs), longer_length(lhs, rhs))
+}
+
+// None if the strings differ in length, like `hamming`
+pub fn hamming_similarity(lhs: &str, rhs: &str) -> Option<f64> {
+    hamming(lhs, rhs).map(|distance| normalize(distance, lhs.chars().count()))
+}
+
+pub fn lcs_similarity(lhs: &str, rhs: &str) -> f64 {
+    normalize(
+        lcs_distance(lhs, rhs),
+        lhs.chars().count() + rhs.chars().count(),
+    )
+}
+
+fn longer_length(lhs: &str, rhs: &str) -> usize {
+    lhs.chars().count().max(rhs.chars().count())
+}
+
+fn normalize(distance: usize, max_distance: usize) -> f64 {
+    if max_distance == 0 {
+        1.0
+    } else {
+        1.0 - distance as f64 / max_distance as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::random_string;
+    use rand::Rng;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "{actual} is not {expected}"
+        );
+    }
+
+    #[test]
+    fn test_transpositions() {
+        assert_eq!(optimal_string_alignment("ca", "abc"), 3);
+        assert_eq!(damerau_levenshtein("ca", "abc"), 2);
+        assert_eq!(levenshtein_distance::calculate("ca", "abc"), 3);
+
+        for (lhs, rhs, expected) in [
+            ("", "", 0),
+            ("", "abc", 3),
+            ("abc", "", 3),
+            ("abc", "abc", 0),
+            ("abc", "acb", 1),
+            ("teh", "the", 1),
+            ("recieve", "receive", 1),
+            ("kitten", "sitting", 3),
+            ("ab", "ba", 1),
+            ("éa東", "é東a", 1),
+        ] {
+            assert_eq!(optimal_string_alignment(lhs, rhs), expected, "{lhs} {rhs}");
+            assert_eq!(damerau_levenshtein(lhs, rhs), expected, "{lhs} {rhs}");
+        }
+
+        // Transposition then an edit on the moved chars
+        assert_eq!(damerau_levenshtein("abcdef", "badcfe"), 3);
+        assert_eq!(damerau_levenshtein("a cat", "an act"), 2);
+        assert_eq!(optimal_string_alignment("a cat", "an act"), 2);
+    }
+
+    #[test]
+    fn test_hamming() {
+        assert_eq!(hamming("", ""), Some(0));
+        assert_eq!(hamming("karolin", "kathrin"), Some(3));
+        assert_eq!(hamming("1011101", "1001001"), Some(2));
+        // Lengths are counted in chars, not bytes
+        assert_eq!(hamming("é東", "e東"), Some(1));
+        assert_eq!(hamming("abc", "ab"), None);
+        assert_eq!(hamming_similarity("karolin", "kathrin"), Some(4.0 / 7.0));
+        assert_eq!(hamming_similarity("", ""), Some(1.0));
+        assert_eq!(hamming_similarity("a", ""), None);
+    }
+
+    #[test]
+    fn test_lcs_distance() {
+        assert_eq!(lcs_distance("", ""), 0);
+        assert_eq!(lcs_distance("abc", ""), 3);
+        // "ittn" in common
+        assert_eq!(lcs_distance("kitten", "sitting"), 5);
+        // A substitution is a deletion and an insertion
+        assert_eq!(lcs_distance("abc", "abd"), 2);
+        assert_close(lcs_similarity("kitten", "sitting"), 8.0 / 13.0);
+    }
+
+    #[test]
+    fn test_jaro() {
+        assert_eq!(jaro_similarity("", ""), 1.0);
+        assert_eq!(jaro_similarity("abc", ""), 0.0);
+        assert_eq!(jaro_similarity("abc", "xyz"), 0.0);
+        assert_eq!(jaro_similarity("abc", "abc"), 1.0);
+        assert_close(jaro_similarity("MARTHA", "MARHTA"), 0.944);
+        assert_close(jaro_similarity("DIXON", "DICKSONX"), 0.767);
+        assert_close(jaro_similarity("DWAYNE", "DUANE"), 0.822);
+        assert_close(jaro_similarity("CRATE", "TRACE"), 0.733);
+
+        assert_close(jaro_winkler_similarity("MARTHA", "MARHTA"), 0.961);
+        assert_close(jaro_winkler_similarity("DIXON", "DICKSONX"), 0.813);
+        assert_close(jaro_winkler_similarity("DWAYNE", "DUANE"), 0.84);
+        // Only the first four chars of the prefix count
+        assert_close(
+            jaro_winkler_similarity("abcdefgh", "abcdefgx"),
+            jaro_similarity("abcdefgh", "abcdefgx") * 0.6 + 0.4,
+        );
+    }
+
+    #[test]
+    fn test_similarities_are_normalized() {
+        assert_eq!(levenshtein_similarity("", ""), 1.0);
+        assert_eq!(levenshtein_similarity("abc", ""), 0.0);
+        assert_close(levenshtein_similarity("kitten", "sitting"), 4.0 / 7.0);
+        assert_close(optimal_string_alignment_similarity("teh", "the"), 2.0 / 3.0);
+        assert_close(damerau_levenshtein_similarity("ca", "abc"), 1.0 / 3.0);
+
+        let mut rng = rand::rng();
+        let similarities: [fn(&str, &str) -> f64; 6] = [
+            levenshtein_similarity,
+            optimal_string_alignment_similarity,
+            damerau_levenshtein_similarity,
+            lcs_similarity,
+            jaro_similarity,
+            jaro_winkler_similarity,
+        ];
+        for _ in 0..200 {
+            let lhs = random_string(rng.random_range(0..10), &['a', 'b', 'é'], &mut rng);
+            let rhs = random_string(rng.random_range(0..10), &['a', 'b', 'é'], &mut rng);
+            for similarity in similarities {
+                let value = similarity(&lhs, &rhs);
+                assert!((0.0..=1.0).contains(&value), "{lhs} {rhs} {value}");
+                assert_eq!(value, similarity(&rhs, &lhs), "{lhs} {rhs}");
+                assert_eq!(similarity(&lhs, &lhs), 1.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+// Brute-force reference implementations the property tests compare against.
+// Each is the most obviously correct way to get the answer, whatever it costs,
+// so the inputs fed to them have to stay small.
+
+pub fn sorted<T: Ord + Clone>(input: &[T]) -> Vec<T> {
+    let mut output = input.to_vec();
+    output.sort();
+    output
+}
+
+// Index of the first element not less than `target`, by linear search
+pub fn lower_bound<T: Ord>(input: &[T], target: &T) -> usize {
+    input
+        .iter()
+        .position(|value| value >= target)
+        .unwrap_or(input.len())
+}
+
+// Index of the first element greater than `target`, by linear search
+pub fn upper_bound<T: Ord>(input: &[T], target: &T) -> usize {
+    input
+        .iter()
+        .position(|value| value > target)
+        .unwrap_or(input.len())
+}
+
+// For every element, the closest index to its left holding a smaller value
+pub fn nearest_smaller_to_the_left<T: Ord>(input: &[T]) -> Vec<Option<usize>> {
+    (0..input.len())
+        .map(|i| (0..i).rev().find(|&j| input[j] < input[i]))
+        .collect()
+}
+
+// The lexicographically smallest (by indices) of the longest strictly increasing
+// subsequences, found by trying every subset of indices
+pub fn longest_increasing_subsequence<T: Ord>(input: &[T]) -> Vec<usize> {
+    assert!(input.len() <= 20, "2^{} subsets is too many", input.len());
+
+    let mut best: Vec<usize> = Vec::new();
+    for mask in 0u32..1 << input.len() {
+        let indices: Vec<usize> = (0..input.len()).filter(|i| mask & 1 << i != 0).collect();
+        let increasing = indices
+            .windows(2)
+            .all(|pair| input[pair[0]] < input[pair[1]]);
+        if increasing
+            && (indices.len() > best.len() || (indices.len() == best.len() && indices < best))
+        {
+            best = indices;
+        }
+    }
+    best
+}
+
+// The leftmost of the longest windows [left, right) whose sum is at most `limit`
+pub fn longest_window_with_sum_at_most(input: &[u32], limit: u32) -> Option<(usize, usize)> {
+    let mut best: Option<(usize, usize)> = None;
+    for left in 0..input.len() {
+        for right in left + 1..=input.len() {
+            let sum: u64 = input[left..right]
+                .iter()
+                .map(|&value| u64::from(value))
+                .sum();
+            let longer = best.is_none_or(|(l, r)| right - left > r - l);
+            if sum <= u64::from(limit) && longer {
+                best = Some((left, right));
+            }
+        }
+    }
+    best
+}
+
+// Edit distance with unit costs, from the full (m + 1) x (n + 1) table
+pub fn edit_distance<T: PartialEq>(lhs: &[T], rhs: &[T]) -> usize {
+    let mut table = vec![vec![0; rhs.len() + 1]; lhs.len() + 1];
+    table[0] = (0..=rhs.len()).collect();
+    for (i, row) in table.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for i in 1..=lhs.len() {
+        for j in 1..=rhs.len() {
+            let substitution = table[i - 1][j - 1] + usize::from(lhs[i - 1] != rhs[j - 1]);
+            table[i][j] = substitution
+                .min(table[i - 1][j] + 1)
+                .min(table[i][j - 1] + 1);
+        }
+    }
+    table[lhs.len()][rhs.len()]
+}
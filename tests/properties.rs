@@ -0,0 +1,364 @@
+// Every algorithm of the crate checked against the brute-force oracles on
+// random inputs. Failing inputs are shrunk by proptest to a minimal case.
+mod oracle;
+
+use cs::stack_with_constant_max::StackWithConstantMax;
+use cs::two_stack_queue::TwoStackQueue;
+use cs::{
+    binary_search, left_nearest_number, levenshtein_distance, longest_increasing_subsequence,
+    longest_subsequence_below_or_equal_m, sort, string_distance,
+};
+use proptest::prelude::*;
+use proptest::test_runner::FileFailurePersistence;
+use std::collections::VecDeque;
+
+type Sort = fn(&mut [i32]);
+
+const SORTS: [(&str, Sort); 16] = [
+    ("selection_sort", sort::selection_sort),
+    ("quick_sort", |input| {
+        sort::quick_sort(input, &mut rand::rng())
+    }),
+    ("lomuto_quick_sort", |input| {
+        sort::lomuto_quick_sort(input, &mut rand::rng())
+    }),
+    ("intro_sort", sort::intro_sort),
+    ("merge_sort", sort::merge_sort),
+    ("stable_merge_sort", sort::stable_merge_sort),
+    ("heap_sort", sort::heap_sort),
+    ("insertion_sort", sort::insertion_sort),
+    ("binary_insertion_sort", sort::binary_insertion_sort),
+    ("shell_sort", |input| {
+        sort::shell_sort(input, sort::ShellGaps::default())
+    }),
+    ("tim_sort", sort::tim_sort),
+    ("lsd_radix_sort", sort::lsd_radix_sort),
+    ("par_quick_sort", |input| {
+        sort::par_quick_sort(input, &parallel_options())
+    }),
+    ("par_merge_sort", |input| {
+        sort::par_merge_sort(input, &parallel_options())
+    }),
+    ("partial_sort", |input| {
+        let len = input.len();
+        sort::partial_sort(input, len)
+    }),
+    ("counting_sort", |input| {
+        sort::counting_sort_by_key(input, |&value| (value - i32::from(i8::MIN)) as usize)
+    }),
+];
+
+// Elements are (key, original position), the sorts only look at the key
+type StableSort = fn(&mut [(i8, usize)]);
+
+const STABLE_SORTS: [(&str, StableSort); 7] = [
+    ("stable_merge_sort", |input| {
+        sort::stable_merge_sort_by_key(input, &mut |element| element.0)
+    }),
+    ("insertion_sort", |input| {
+        sort::insertion_sort_by_key(input, &mut |element| element.0)
+    }),
+    ("binary_insertion_sort", |input| {
+        sort::binary_insertion_sort_by_key(input, &mut |element| element.0)
+    }),
+    ("tim_sort", |input| {
+        sort::tim_sort_by_key(input, &mut |element| element.0)
+    }),
+    ("par_merge_sort", |input| {
+        sort::par_merge_sort_by_key(input, &parallel_options(), &|element| element.0)
+    }),
+    ("lsd_radix_sort", |input| {
+        sort::lsd_radix_sort_by_key(input, |element| element.0)
+    }),
+    ("counting_sort", |input| {
+        sort::counting_sort_by_key(input, |element| {
+            (element.0 as i16 - i16::from(i8::MIN)) as usize
+        })
+    }),
+];
+
+// Tiny cutoffs so that short inputs go through the threads as well
+fn parallel_options() -> sort::ParallelOptions {
+    sort::ParallelOptions {
+        threads: 4,
+        sequential_cutoff: 4,
+    }
+}
+
+// Small values make duplicates, which most of the bugs are about
+fn values() -> impl Strategy<Value = Vec<i32>> {
+    prop::collection::vec(-20..20i32, 0..200)
+}
+
+fn bytes() -> impl Strategy<Value = Vec<i32>> {
+    prop::collection::vec(i32::from(i8::MIN)..=i32::from(i8::MAX), 0..200)
+}
+
+proptest! {
+    // Failing cases are saved next to this file and replayed first on later runs
+    #![proptest_config(ProptestConfig {
+        failure_persistence: Some(Box::new(FileFailurePersistence::WithSource("regressions"))),
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn sorts_match_slice_sort(input in values()) {
+        let expected = oracle::sorted(&input);
+        for (name, sort) in SORTS {
+            let mut output = input.clone();
+            sort(&mut output);
+            prop_assert_eq!(&output, &expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn sorts_of_full_range_values(input in prop::collection::vec(any::<i32>(), 0..100)) {
+        let expected = oracle::sorted(&input);
+        // Counting sort would need a count for every i32
+        for (name, sort) in &SORTS[..SORTS.len() - 1] {
+            let mut output = input.clone();
+            sort(&mut output);
+            prop_assert_eq!(&output, &expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn stable_sorts_keep_the_order_of_equal_keys(keys in prop::collection::vec(-3..3i8, 0..200)) {
+        let input: Vec<(i8, usize)> = keys.into_iter().zip(0..).collect();
+        // slice::sort is stable, and the positions are all different
+        let expected = oracle::sorted(&input);
+        for (name, sort) in STABLE_SORTS {
+            let mut output = input.clone();
+            sort(&mut output);
+            prop_assert_eq!(&output, &expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn byte_strings_sort(input in prop::collection::vec(prop::collection::vec(0..4u8, 0..6), 0..100)) {
+        let mut output = input.clone();
+        sort::msd_radix_sort(&mut output);
+        prop_assert_eq!(output, oracle::sorted(&input));
+    }
+
+    #[test]
+    fn bucket_sort_matches_total_order(input in prop::collection::vec(any::<f64>(), 0..100)) {
+        let mut output = input.clone();
+        sort::bucket_sort(&mut output);
+        let mut expected = input;
+        expected.sort_by(f64::total_cmp);
+        prop_assert_eq!(
+            output.iter().map(|value| value.to_bits()).collect::<Vec<_>>(),
+            expected.iter().map(|value| value.to_bits()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn nth_element_puts_the_sorted_element_in_place(input in values(), n in any::<prop::sample::Index>()) {
+        prop_assume!(!input.is_empty());
+        let n = n.index(input.len());
+        let expected = oracle::sorted(&input);
+
+        let mut output = input.clone();
+        sort::nth_element(&mut output, n);
+        prop_assert_eq!(output[n], expected[n]);
+        prop_assert!(output[..n].iter().all(|value| *value <= output[n]));
+        prop_assert!(output[n + 1..].iter().all(|value| *value >= output[n]));
+
+        let mut output = input.clone();
+        prop_assert_eq!(*sort::quickselect(&mut output, n), expected[n]);
+    }
+
+    #[test]
+    fn smallest_k(input in bytes(), k in 0..250usize) {
+        let expected = oracle::sorted(&input);
+        let k_expected = &expected[..k.min(input.len())];
+
+        let mut output = input.clone();
+        sort::partial_sort(&mut output, k);
+        prop_assert_eq!(&output[..k.min(input.len())], k_expected);
+        prop_assert_eq!(oracle::sorted(&output), expected.clone());
+
+        prop_assert_eq!(sort::top_k(input.iter().copied(), k), k_expected);
+    }
+
+    #[test]
+    fn merges_of_sorted_runs(runs in prop::collection::vec(bytes(), 0..8)) {
+        let runs: Vec<Vec<i32>> = runs.iter().map(|run| oracle::sorted(run)).collect();
+        let expected = oracle::sorted(&runs.concat());
+
+        let merged: Vec<i32> = sort::KWayMerge::new(runs.clone()).collect();
+        prop_assert_eq!(&merged, &expected);
+
+        if let [lhs, rhs, ..] = &runs[..] {
+            let merged: Vec<i32> = sort::merge(lhs.iter().copied(), rhs.iter().copied()).collect();
+            prop_assert_eq!(merged, oracle::sorted(&[lhs.clone(), rhs.clone()].concat()));
+        }
+    }
+
+    #[test]
+    fn bounds_match_linear_search(input in values(), target in -25..25i32) {
+        let input = oracle::sorted(&input);
+        let lower = oracle::lower_bound(&input, &target);
+        let upper = oracle::upper_bound(&input, &target);
+
+        prop_assert_eq!(binary_search::lower_bound(&input, &target), lower);
+        prop_assert_eq!(binary_search::upper_bound(&input, &target), upper);
+        prop_assert_eq!(binary_search::equal_range(&input, &target), lower..upper);
+        prop_assert_eq!(
+            binary_search::partition_point(&input, |value| *value < target),
+            lower
+        );
+    }
+
+    #[test]
+    fn nearest_smaller_matches_quadratic_scan(input in values()) {
+        prop_assert_eq!(
+            left_nearest_number::find(&input),
+            oracle::nearest_smaller_to_the_left(&input)
+        );
+    }
+
+    #[test]
+    fn longest_increasing_subsequence_matches_exhaustive_search(
+        input in prop::collection::vec(0..8i32, 0..14)
+    ) {
+        let expected = oracle::longest_increasing_subsequence(&input);
+        // The algorithms report no subsequence rather than a single element
+        let expected = (expected.len() > 1).then_some(expected);
+
+        let found = longest_increasing_subsequence::find(&input);
+        prop_assert_eq!(found.as_ref().map(Vec::len), expected.as_ref().map(Vec::len));
+        if let Some(found) = found {
+            prop_assert!(found.windows(2).all(|pair| pair[0] < pair[1]));
+            prop_assert!(found.windows(2).all(|pair| input[pair[0]] < input[pair[1]]));
+        }
+
+        prop_assert_eq!(
+            longest_increasing_subsequence::find_lexicographically_smallest(&input),
+            expected
+        );
+    }
+
+    #[test]
+    fn longest_window_matches_all_windows(
+        input in prop::collection::vec(0..10u32, 0..60),
+        limit in 0..40u32
+    ) {
+        let found = longest_subsequence_below_or_equal_m::find(&input, limit)
+            .map(|output| (output.left, output.right));
+        prop_assert_eq!(found, oracle::longest_window_with_sum_at_most(&input, limit));
+    }
+
+    #[test]
+    fn edit_distance_matches_full_table(lhs in "[abé東😀]{0,12}", rhs in "[abé東😀]{0,12}") {
+        let lhs_chars: Vec<char> = lhs.chars().collect();
+        let rhs_chars: Vec<char> = rhs.chars().collect();
+        prop_assert_eq!(
+            levenshtein_distance::calculate(&lhs, &rhs),
+            oracle::edit_distance(&lhs_chars, &rhs_chars)
+        );
+        prop_assert_eq!(
+            levenshtein_distance::calculate_bytes(&lhs, &rhs),
+            oracle::edit_distance(lhs.as_bytes(), rhs.as_bytes())
+        );
+    }
+
+    // Long enough for the pattern to span several 64-bit blocks
+    #[test]
+    fn bit_parallel_distance_matches_dp(
+        lhs in prop::collection::vec(0..4u8, 0..200),
+        rhs in prop::collection::vec(0..4u8, 0..200)
+    ) {
+        prop_assert_eq!(
+            levenshtein_distance::bit_parallel_distance(&lhs, &rhs),
+            oracle::edit_distance(&lhs, &rhs)
+        );
+        prop_assert_eq!(
+            levenshtein_distance::distance(&lhs, &rhs, &levenshtein_distance::UnitCosts),
+            oracle::edit_distance(&lhs, &rhs)
+        );
+    }
+
+    #[test]
+    fn bounded_distance_agrees_within_the_bound(
+        lhs in "[abé]{0,12}",
+        rhs in "[abé]{0,12}",
+        max in 0..14usize
+    ) {
+        let lhs_chars: Vec<char> = lhs.chars().collect();
+        let rhs_chars: Vec<char> = rhs.chars().collect();
+        let distance = oracle::edit_distance(&lhs_chars, &rhs_chars);
+        prop_assert_eq!(
+            levenshtein_distance::calculate_bounded(&lhs, &rhs, max),
+            (distance <= max).then_some(distance)
+        );
+    }
+
+    // Each distance allows a superset of the edits of the next one
+    #[test]
+    fn string_distances_are_ordered(lhs in "[abc]{0,10}", rhs in "[abc]{0,10}") {
+        let damerau_levenshtein = string_distance::damerau_levenshtein(&lhs, &rhs);
+        let optimal_string_alignment = string_distance::optimal_string_alignment(&lhs, &rhs);
+        let levenshtein = levenshtein_distance::calculate(&lhs, &rhs);
+        let lcs = string_distance::lcs_distance(&lhs, &rhs);
+        prop_assert!(damerau_levenshtein <= optimal_string_alignment);
+        prop_assert!(optimal_string_alignment <= levenshtein);
+        prop_assert!(levenshtein <= lcs);
+        prop_assert_eq!(damerau_levenshtein, string_distance::damerau_levenshtein(&rhs, &lhs));
+        if let Some(hamming) = string_distance::hamming(&lhs, &rhs) {
+            prop_assert!(levenshtein <= hamming);
+        }
+    }
+
+    #[test]
+    fn edit_script_is_a_shortest_one(lhs in "[abc]{0,12}", rhs in "[abc]{0,12}") {
+        let alignment = levenshtein_distance::align(&lhs, &rhs);
+        prop_assert_eq!(alignment.lhs.replace(levenshtein_distance::GAP, ""), lhs.clone());
+        prop_assert_eq!(alignment.rhs.replace(levenshtein_distance::GAP, ""), rhs.clone());
+
+        let cost = alignment
+            .lhs
+            .chars()
+            .zip(alignment.rhs.chars())
+            .filter(|(top, bottom)| top != bottom)
+            .count();
+        let lhs_chars: Vec<char> = lhs.chars().collect();
+        let rhs_chars: Vec<char> = rhs.chars().collect();
+        prop_assert_eq!(cost, oracle::edit_distance(&lhs_chars, &rhs_chars));
+    }
+
+    // Some(value) pushes, None pops
+    #[test]
+    fn stack_matches_vec(operations in prop::collection::vec(prop::option::of(-50..50i32), 0..100)) {
+        let mut stack = StackWithConstantMax::new();
+        let mut model = Vec::new();
+        for operation in operations {
+            match operation {
+                Some(value) => {
+                    stack.push(value);
+                    model.push(value);
+                }
+                None => prop_assert_eq!(stack.pop(), model.pop()),
+            }
+            prop_assert_eq!(stack.max(), model.iter().max().copied());
+            prop_assert_eq!(stack.is_empty(), model.is_empty());
+        }
+    }
+
+    #[test]
+    fn queue_matches_vec_deque(operations in prop::collection::vec(prop::option::of(-50..50i32), 0..100)) {
+        let mut queue = TwoStackQueue::new();
+        let mut model = VecDeque::new();
+        for operation in operations {
+            match operation {
+                Some(value) => {
+                    queue.push(value);
+                    model.push_back(value);
+                }
+                None => prop_assert_eq!(queue.pop(), model.pop_front()),
+            }
+            prop_assert_eq!(queue.max(), model.iter().max().copied());
+        }
+    }
+}